@@ -0,0 +1,165 @@
+use crate::explorer::format_time;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::widgets::{
+    Block, Borders, Clear, Paragraph, Row, StatefulWidget, Table, TableState, Widget, Wrap,
+};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// how long a toast stays on screen
+const SHOWN: Duration = Duration::from_secs(5);
+/// toasts drawn at the same time, the oldest go first
+const MAX_SHOWN: usize = 4;
+/// alerts kept in the alerts panel, older ones are dropped
+const KEEP: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Severity {
+    fn color(self) -> Color {
+        match self {
+            Severity::Info => Color::Cyan,
+            Severity::Warn => Color::Yellow,
+            Severity::Error => Color::Red,
+        }
+    }
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warn => "warn",
+            Severity::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Alert {
+    pub severity: Severity,
+    pub text: String,
+    raised: Instant,
+    /// unix seconds, for the alerts panel
+    at: u64,
+}
+
+/// events worth noticing wherever the user is looking, shown as a toast for a few
+/// seconds and kept in the alerts panel
+#[derive(Debug, Default)]
+pub struct Alerts {
+    pub list: Vec<Alert>,
+    /// raised since the alerts panel was last looked at
+    unread: usize,
+}
+
+impl Alerts {
+    pub fn raise(&mut self, severity: Severity, text: String) {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.list.push(Alert {
+            severity,
+            text,
+            raised: Instant::now(),
+            at,
+        });
+        if self.list.len() > KEEP {
+            self.list.remove(0);
+        }
+        self.unread = (self.unread + 1).min(self.list.len());
+    }
+    pub fn unread(&self) -> usize {
+        self.unread
+    }
+    pub fn mark_read(&mut self) {
+        self.unread = 0;
+    }
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.unread = 0;
+    }
+    /// the alerts of the last few seconds stacked in the top right corner of `area`
+    pub fn render_toasts(&self, area: Rect, buf: &mut Buffer) {
+        let width = area.width.min(48);
+        let inner = width.saturating_sub(2).max(1) as usize;
+        let mut y = area.y + 1;
+        let live = self.list.iter().filter(|a| a.raised.elapsed() < SHOWN);
+        for alert in live.rev().take(MAX_SHOWN) {
+            let height = (alert.text.chars().count().div_ceil(inner) as u16).clamp(1, 3) + 2;
+            if y + height > area.bottom() {
+                break;
+            }
+            let popup = Rect::new(area.right().saturating_sub(width + 1), y, width, height);
+            Clear.render(popup, buf);
+            Paragraph::new(alert.text.clone())
+                .block(
+                    Block::new()
+                        .title(format!(" {} ", alert.severity.label()))
+                        .borders(Borders::ALL)
+                        .border_style(alert.severity.color()),
+                )
+                .wrap(Wrap { trim: true })
+                .bold()
+                .render(popup, buf);
+            y += height;
+        }
+    }
+}
+
+/// the alerts newest first
+#[derive(Debug, Default)]
+pub struct AlertsPanel {
+    state: TableState,
+    selected: usize,
+}
+
+impl AlertsPanel {
+    pub fn select(&mut self, step: isize, count: usize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(step)
+            .min(count.saturating_sub(1));
+    }
+    pub fn render(&mut self, block: Block, alerts: &Alerts, area: Rect, buf: &mut Buffer) {
+        self.selected = self.selected.min(alerts.list.len().saturating_sub(1));
+        let rows = alerts
+            .list
+            .iter()
+            .rev()
+            .map(|alert| {
+                Row::new(vec![
+                    format_time(alert.at),
+                    alert.severity.label().to_string(),
+                    alert.text.clone(),
+                ])
+                .fg(alert.severity.color())
+            })
+            .collect::<Vec<_>>();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(17),
+                Constraint::Length(6),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec!["TIME (UTC)", "LEVEL", "MESSAGE"])
+                .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .block(block)
+        .row_highlight_style(
+            Style::new()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+        self.state
+            .select(Some(self.selected).filter(|_| !alerts.list.is_empty()));
+        StatefulWidget::render(table, area, buf, &mut self.state);
+    }
+}
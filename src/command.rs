@@ -1,6 +1,12 @@
+use crate::params;
+use crate::triggers;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::collections::BTreeMap;
 use std::fs::{self, File};
-use std::io::{BufWriter, Result, Write};
+use std::io::{BufWriter, Error, ErrorKind, Result, Write};
+use std::path::Path;
 use std::str::FromStr;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -14,18 +20,464 @@ pub struct CmdInfo {
     pub work_dir: String,
     #[serde(default)]
     pub args: Vec<String>,
+    /// services that must be running (and ready) before this one starts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<MenuCommand>,
+    /// output substring that marks the service as ready, empty means ready once spawned
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ready_pattern: String,
+    /// start this service as soon as the tool launches
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub autostart: bool,
+    /// seconds to wait before autostarting
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub autostart_delay: u64,
+    /// where the child's stdin comes from
+    #[serde(default, skip_serializing_if = "StdinMode::is_null")]
+    pub stdin: StdinMode,
+    /// run under a pseudo terminal so the program sees a tty, `stdin` is ignored then
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pty: bool,
+    /// resource usage of the service's process tree that needs attention
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thresholds: Option<Thresholds>,
+    /// resources the command may use
+    #[serde(default, skip_serializing_if = "Limits::is_empty")]
+    pub limits: Limits,
+    /// run these one after another instead of `exe_path`, one-shot commands only
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<Step>,
+    /// `pip freeze` this service's python before and after every pipeline and custom nodes update
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pip_snapshots: bool,
+    /// options the args form shows as controls instead of raw arguments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<Flag>,
+    /// values asked for on Enter, `{name}` in `exe_path`, `work_dir`, `args`, steps and hooks
+    /// is replaced
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<Param>,
+    /// seconds a one-shot command, or every `run` and `git` step of it, may take before it
+    /// is stopped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// runs a failed one-shot command again, with steps the failed `run` step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<Retry>,
+    /// commands run around the start and stop of a service
+    #[serde(default, skip_serializing_if = "Hooks::is_empty")]
+    pub hooks: Hooks,
+    /// output lines of a service that set off an action
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<OutputTrigger>,
+    /// service panel that shows the output of a `Custom` entry, comfyui when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panel: Option<LogPanel>,
+}
+impl CmdInfo {
+    /// tells the menu entries apart, `Custom` ones by their name
+    pub fn key(&self) -> String {
+        entry_key(self.command, &self.name)
+    }
+}
+
+fn entry_key(command: MenuCommand, name: &str) -> String {
+    format!("{:?}/{}", command, name.trim())
+}
+
+/// how often and when a failed run is tried again
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Retry {
+    /// attempts after the first one
+    pub count: u32,
+    /// seconds between attempts
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub delay: u64,
+    /// exit codes worth another attempt, any failure when empty, a timeout always is
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on: Vec<i32>,
+}
+
+/// a placeholder filled in before the command runs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Param {
+    pub name: String,
+    /// question shown above the input, `name` when empty
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub prompt: String,
+    /// used until a value has been typed once
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub default: String,
+    /// the only values accepted, ▲ ▼ go through them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<String>,
+    /// regex the whole value has to match
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pattern: String,
+}
+
+/// one launch option, present in `args` when it is on
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Flag {
+    pub name: String,
+    /// `--lowvram`, followed by its value unless it is a toggle
+    pub flag: String,
+    #[serde(default)]
+    pub kind: FlagKind,
+}
+
+/// `"toggle"`, `{"choice": ["auto", "none"]}` or `"text"`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagKind {
+    #[default]
+    Toggle,
+    Choice(Vec<String>),
+    Text,
+}
+
+/// one entry of a pipeline, a program to run or an action on a service
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Step {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: StepKind,
+    /// go on with the next step when this one fails
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub continue_on_error: bool,
+}
+impl Step {
+    pub fn label(&self) -> String {
+        if !self.name.is_empty() {
+            return self.name.clone();
+        }
+        match &self.kind {
+            StepKind::Service { service, action } => format!("{:?} {:?}", action, service),
+            StepKind::Git { git, .. } => format!("git {:?}", git).to_lowercase(),
+            StepKind::Run { exe_path, args, .. } => format!("{} {}", exe_path, args.join(" "))
+                .trim_end()
+                .to_string(),
+        }
+    }
+}
+
+/// `{"service": "ComfyRun", "action": "stop"}`, `{"git": "update"}`
+/// or `{"exe_path": "git", "args": ["pull"]}`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum StepKind {
+    Service {
+        service: MenuCommand,
+        action: ServiceAction,
+    },
+    Git {
+        git: GitAction,
+        #[serde(default)]
+        dirty: DirtyPolicy,
+        /// the command's `work_dir` when empty
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        work_dir: String,
+    },
+    Run {
+        exe_path: String,
+        #[serde(default)]
+        args: Vec<String>,
+        /// the command's `work_dir` when empty
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        work_dir: String,
+    },
+}
+
+/// `update` fetches and fast-forwards, remembering the old commit for a rollback
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum GitAction {
+    Update,
+}
+
+/// what a git update does with uncommitted changes
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DirtyPolicy {
+    #[default]
+    Refuse,
+    Stash,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+/// lifecycle hooks of a service, their output goes to the service's log panel
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Hooks {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_start: Option<Hook>,
+    /// once the service reports ready
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_start: Option<Hook>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_stop: Option<Hook>,
+    /// after a stop from the tool, restarts and quitting included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_stop: Option<Hook>,
+    /// after the service exited on its own with a failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_crash: Option<Hook>,
+}
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        *self == Hooks::default()
+    }
+    /// every hook with its name
+    pub fn all(&self) -> [(&'static str, Option<&Hook>); 5] {
+        [
+            ("pre_start", self.pre_start.as_ref()),
+            ("post_start", self.post_start.as_ref()),
+            ("pre_stop", self.pre_stop.as_ref()),
+            ("post_stop", self.post_stop.as_ref()),
+            ("on_crash", self.on_crash.as_ref()),
+        ]
+    }
+    pub fn all_mut(&mut self) -> [&mut Option<Hook>; 5] {
+        [
+            &mut self.pre_start,
+            &mut self.post_start,
+            &mut self.pre_stop,
+            &mut self.post_stop,
+            &mut self.on_crash,
+        ]
+    }
+    /// the hooks with `work_dir` filled in where they left it empty
+    pub fn in_dir(&self, work_dir: &str) -> Hooks {
+        let mut hooks = self.clone();
+        for hook in hooks.all_mut().into_iter().flatten() {
+            if hook.work_dir.is_empty() {
+                hook.work_dir = work_dir.to_string();
+            }
+        }
+        hooks
+    }
+}
+
+/// an output line of a service matching `pattern` sets off `action`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OutputTrigger {
+    /// regex, `$1` or `${name}` in the toast text and the run arguments are filled from
+    /// its groups, any other `$` stays as it is
+    pub pattern: String,
+    pub action: TriggerAction,
+    /// seconds the trigger stays quiet after it went off, restarts of the service included
+    #[serde(default = "default_cooldown")]
+    pub cooldown: u64,
+}
+
+fn default_cooldown() -> u64 {
+    30
+}
+
+/// `"ready"`, `"restart"`, `"bell"`, `"flash"`, `{"toast": "text"}` or `{"run": {...}}`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerAction {
+    /// marks the service ready, like `ready_pattern`
+    Ready,
+    Restart,
+    /// rings the terminal bell
+    Bell,
+    /// blinks the border of the service's log panel
+    Flash,
+    /// raises an info alert, shown as a toast and kept in the alerts panel
+    Toast(String),
+    /// a program like a hook's, its output goes to the service's log panel
+    Run(Hook),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// one program run by a lifecycle hook
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Hook {
+    pub exe_path: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// set on top of the tool's environment
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// the service's `work_dir` when empty
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub work_dir: String,
+    /// seconds before the hook is stopped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// a failed `pre_start` or `pre_stop` cancels the start or stop
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub abort: bool,
+}
+
+/// set up in the child before it execs the program, so whatever it starts is limited too,
+/// unset fields leave the inherited value
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Limits {
+    /// scheduling niceness, -20 runs first and 19 last, a priority class on windows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i32>,
+    /// cores the command may run on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cpus: Vec<usize>,
+    /// RLIMIT_AS, allocations beyond it fail inside the process
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_space_mb: Option<u64>,
+    /// memory.max of a cgroup v2 sub-group, the kernel reclaims and then oom kills beyond it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// RLIMIT_NOFILE
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_files: Option<u64>,
+}
+impl Limits {
+    pub fn is_empty(&self) -> bool {
+        *self == Limits::default()
+    }
+}
+
+/// limits checked against the once a second usage samples of a service
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Thresholds {
+    /// summed over the tree, 100 is one full core
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_percent: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss_mb: Option<u64>,
+    /// seconds a limit has to be exceeded in a row before `action` runs
+    #[serde(default = "default_sustain")]
+    pub sustain: u64,
+    #[serde(default)]
+    pub action: ThresholdAction,
+}
+
+fn default_sustain() -> u64 {
+    5
+}
+
+/// what happens once a threshold is exceeded for long enough
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ThresholdAction {
+    #[default]
+    Warn,
+    Restart,
+}
+
+/// git checkouts of comfyui extensions, managed from the nodes panel
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CustomNodes {
+    /// folder whose sub folders are scanned for repositories
+    pub dir: String,
+    /// interpreter that installs a changed requirements.txt, ComfyRun's `exe_path` when empty
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub python: String,
+    /// repositories updated at the same time
+    #[serde(default = "default_parallel")]
+    pub parallel: usize,
+}
+
+fn default_parallel() -> usize {
+    4
+}
+
+fn default_max_jobs() -> usize {
+    2
+}
+
+/// `"null"`, `"piped"` to type into it from the log panel, or `{"file": "path"}`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StdinMode {
+    #[default]
+    Null,
+    Piped,
+    File(String),
+}
+impl StdinMode {
+    fn is_null(&self) -> bool {
+        *self == StdinMode::Null
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MenuCommand {
     ComfyRun,
     ComfyUpdate,
     ComfyKill,
+    ComfyRestart,
+    ComfyPause,
+    ComfySignal,
+    ComfyNodes,
     CronRun,
     CronKill,
+    CronRestart,
+    CronPause,
+    CronSignal,
+    StartAll,
+    StopAll,
+    Packages,
     Config,
     About,
     Exit,
+    /// a one-shot command or pipeline of the user's own, any number of them
+    Custom,
+}
+
+impl MenuCommand {
+    /// every command in menu order, for picking one in the editor
+    pub const ALL: [MenuCommand; 19] = [
+        Self::ComfyRun,
+        Self::ComfyUpdate,
+        Self::ComfyKill,
+        Self::ComfyRestart,
+        Self::ComfyPause,
+        Self::ComfySignal,
+        Self::ComfyNodes,
+        Self::CronRun,
+        Self::CronKill,
+        Self::CronRestart,
+        Self::CronPause,
+        Self::CronSignal,
+        Self::StartAll,
+        Self::StopAll,
+        Self::Packages,
+        Self::Config,
+        Self::About,
+        Self::Exit,
+        Self::Custom,
+    ];
+    /// long running commands that own a pid and a log panel
+    pub fn is_service(&self) -> bool {
+        matches!(self, Self::ComfyRun | Self::CronRun)
+    }
+    /// the service a kill, restart, pause or signal command acts on
+    pub fn service_of(&self) -> Option<MenuCommand> {
+        match self {
+            Self::ComfyKill | Self::ComfyRestart | Self::ComfyPause | Self::ComfySignal => {
+                Some(Self::ComfyRun)
+            }
+            Self::CronKill | Self::CronRestart | Self::CronPause | Self::CronSignal => {
+                Some(Self::CronRun)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl FromStr for MenuCommand {
@@ -36,27 +488,87 @@ impl FromStr for MenuCommand {
             "ComfyRun" => Ok(Self::ComfyRun),
             "ComfyUpdate" => Ok(Self::ComfyUpdate),
             "ComfyKill" => Ok(Self::ComfyKill),
+            "ComfyRestart" => Ok(Self::ComfyRestart),
+            "ComfyPause" => Ok(Self::ComfyPause),
+            "ComfySignal" => Ok(Self::ComfySignal),
+            "ComfyNodes" => Ok(Self::ComfyNodes),
             "CronRun" => Ok(Self::CronRun),
             "CronKill" => Ok(Self::CronKill),
+            "CronRestart" => Ok(Self::CronRestart),
+            "CronPause" => Ok(Self::CronPause),
+            "CronSignal" => Ok(Self::CronSignal),
+            "StartAll" => Ok(Self::StartAll),
+            "StopAll" => Ok(Self::StopAll),
+            "Packages" => Ok(Self::Packages),
             "Config" => Ok(Self::Config),
             "About" => Ok(Self::About),
             "Exit" => Ok(Self::Exit),
+            "Custom" => Ok(Self::Custom),
             _ => Err(()),
         }
     }
 }
 
+/// log panel that receives a quick action's output
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogPanel {
+    #[default]
+    Debug,
+    Comfyui,
+    Cron,
+}
+
+/// diagnostic shortcut bound to a single key
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct QuickAction {
+    pub key: char,
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// run `command` and `args` as one line through `sh -c`, or `pwsh -Command` on windows
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub shell: bool,
+    #[serde(default)]
+    pub panel: LogPanel,
+}
+
+/// keys handled by the tool itself, quick actions cannot use them
+pub const RESERVED_KEYS: &[char] = &[
+    'q', 'd', 'c', 'i', 'p', 'r', 'n', 'a', 'e', '+', 'h', 'j', 'l',
+];
+
+/// what quitting does with services that are still running
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnExit {
+    Stop,
+    Detach,
+    #[default]
+    Ask,
+}
+
 const FILEPATH: &str = "tool.json";
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Config {
     pub fps: f32,
     pub limit: usize,
+    #[serde(default)]
+    pub on_exit: OnExit,
+    /// one-shot commands and quick actions running at the same time, more wait in the queue
+    #[serde(default = "default_max_jobs")]
+    pub max_jobs: usize,
     pub commands: Vec<CmdInfo>,
+    #[serde(default)]
+    pub quick_actions: Vec<QuickAction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_nodes: Option<CustomNodes>,
 }
 
 impl Config {
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self> {
         let config = match fs::exists(FILEPATH) {
             Ok(_) => match Self::read() {
                 Ok(cnfg) => cnfg,
@@ -64,15 +576,358 @@ impl Config {
             },
             Err(_) => Self::write_default(),
         };
-        Self {
-            commands: config.commands,
-            fps: config.fps,
-            limit: config.limit,
+        config.check()?;
+        Ok(config)
+    }
+    /// everything `new` refuses, run again on the commands edited in the menu
+    pub fn check(&self) -> Result<()> {
+        self.check_commands()?;
+        self.service_order()?;
+        self.check_quick_actions()?;
+        self.check_steps()?;
+        self.check_snapshots()?;
+        self.check_params()?;
+        self.check_hooks()?;
+        self.check_triggers()
+    }
+    fn check_commands(&self) -> Result<()> {
+        for (i, cmd) in self.commands.iter().enumerate() {
+            if cmd.name.trim().is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("entry {} of the menu has no name", i + 1),
+                ));
+            }
+            // args, jobs and pipeline progress are kept per entry
+            let custom = cmd.command == MenuCommand::Custom;
+            let earlier = &self.commands[..i];
+            if custom && earlier.iter().any(|c| c.key() == cmd.key()) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("custom entry {:?} is in the menu twice", cmd.name.trim()),
+                ));
+            }
+            if !custom && earlier.iter().any(|c| c.command == cmd.command) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "{:?} can only be in the menu once, add Custom entries for your own commands",
+                        cmd.command
+                    ),
+                ));
+            }
+            if custom {
+                if cmd.exe_path.trim().is_empty() && cmd.steps.is_empty() {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "custom entry {:?} needs a program to run or steps",
+                            cmd.name.trim()
+                        ),
+                    ));
+                }
+                if cmd.panel == Some(LogPanel::Debug) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "custom entry {:?} shows its output in the comfyui or cron panel",
+                            cmd.name.trim()
+                        ),
+                    ));
+                }
+            } else if cmd.panel.is_some() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "{:?} shows its output in its own panel, only Custom entries pick one",
+                        cmd.command
+                    ),
+                ));
+            }
+            if !cmd.command.is_service() {
+                continue;
+            }
+            if cmd.timeout.is_some() || cmd.retry.is_some() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "{:?} is a service and cannot have a timeout or retry",
+                        cmd.command
+                    ),
+                ));
+            }
+            if cmd.exe_path.trim().is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{:?} is a service and needs a program to run", cmd.command),
+                ));
+            }
         }
+        Ok(())
+    }
+    fn check_steps(&self) -> Result<()> {
+        for cmd in self.commands.iter().filter(|c| !c.steps.is_empty()) {
+            if cmd.command.is_service() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{:?} is a service and cannot have steps", cmd.command),
+                ));
+            }
+            for step in &cmd.steps {
+                let StepKind::Service { service, .. } = step.kind else {
+                    continue;
+                };
+                if !service.is_service() || self.find(service).is_none() {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "step {:?} of {:?} needs a configured service, not {:?}",
+                            step.label(),
+                            cmd.command,
+                            service
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+    fn check_snapshots(&self) -> Result<()> {
+        for cmd in self.commands.iter().filter(|c| c.pip_snapshots) {
+            let python = Path::new(&cmd.exe_path)
+                .file_stem()
+                .is_some_and(|stem| stem.to_string_lossy().starts_with("python"));
+            if !cmd.command.is_service() || !python {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "{:?} has pip_snapshots but is not a service run by a python interpreter",
+                        cmd.command
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+    fn check_params(&self) -> Result<()> {
+        for cmd in &self.commands {
+            for (i, param) in cmd.params.iter().enumerate() {
+                if cmd.params[..i].iter().any(|p| p.name == param.name) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "param {:?} of {:?} is declared twice",
+                            param.name, cmd.command
+                        ),
+                    ));
+                }
+                if let Err(err) = Regex::new(&param.pattern) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "param {:?} of {:?} has an invalid pattern :{}",
+                            param.name, cmd.command, err
+                        ),
+                    ));
+                }
+                // an empty default leaves the prompt empty, there is nothing to check
+                if param.default.is_empty() {
+                    continue;
+                }
+                if let Err(err) = params::validate(param, &param.default) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "default {:?} of param {:?} of {:?} is not accepted, {}",
+                            param.default, param.name, cmd.command, err
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+    fn check_hooks(&self) -> Result<()> {
+        for cmd in self.commands.iter().filter(|c| !c.hooks.is_empty()) {
+            if !cmd.command.is_service() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{:?} is not a service and cannot have hooks", cmd.command),
+                ));
+            }
+            for (name, hook) in cmd.hooks.all() {
+                let Some(hook) = hook else {
+                    continue;
+                };
+                if hook.exe_path.trim().is_empty() {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("hook {} of {:?} has no program to run", name, cmd.command),
+                    ));
+                }
+                if hook.abort && !name.starts_with("pre_") {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "hook {} of {:?} cannot abort, only pre_start and pre_stop can",
+                            name, cmd.command
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+    fn check_triggers(&self) -> Result<()> {
+        for cmd in self.commands.iter().filter(|c| !c.triggers.is_empty()) {
+            if !cmd.command.is_service() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "{:?} is not a service and cannot have triggers",
+                        cmd.command
+                    ),
+                ));
+            }
+            for trigger in &cmd.triggers {
+                let pattern = match Regex::new(&trigger.pattern) {
+                    Ok(pattern) => pattern,
+                    Err(err) => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            format!(
+                                "trigger {:?} of {:?} has an invalid pattern :{}",
+                                trigger.pattern, cmd.command, err
+                            ),
+                        ))
+                    }
+                };
+                if let Some(group) = triggers::unknown_group(&trigger.action, &pattern) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "trigger {:?} of {:?} uses group {:?}, which its pattern does not have",
+                            trigger.pattern, cmd.command, group
+                        ),
+                    ));
+                }
+                let TriggerAction::Run(hook) = &trigger.action else {
+                    continue;
+                };
+                if hook.exe_path.trim().is_empty() || hook.abort {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "trigger {:?} of {:?} needs a program to run and cannot abort",
+                            trigger.pattern, cmd.command
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+    fn check_quick_actions(&self) -> Result<()> {
+        for (i, action) in self.quick_actions.iter().enumerate() {
+            if RESERVED_KEYS.contains(&action.key) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "quick action {:?} uses reserved key '{}'",
+                        action.name, action.key
+                    ),
+                ));
+            }
+            if self.quick_actions[..i].iter().any(|a| a.key == action.key) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("quick action key '{}' is bound twice", action.key),
+                ));
+            }
+        }
+        Ok(())
+    }
+    pub fn find(&self, command: MenuCommand) -> Option<&CmdInfo> {
+        self.commands.iter().find(|c| c.command == command)
+    }
+    /// every service ordered so that dependencies come before their dependents,
+    /// fails on unknown dependencies and on cycles
+    pub fn service_order(&self) -> Result<Vec<MenuCommand>> {
+        let mut order = Vec::new();
+        for cmd in self.commands.iter().filter(|c| c.command.is_service()) {
+            self.visit(cmd.command, &mut Vec::new(), &mut order)?;
+        }
+        Ok(order)
+    }
+    /// `command` and everything it depends on, dependencies first
+    pub fn start_order(&self, command: MenuCommand) -> Vec<MenuCommand> {
+        let mut order = Vec::new();
+        let _ = self.visit(command, &mut Vec::new(), &mut order);
+        order
+    }
+    /// services that depend on `command` directly or indirectly, dependents first
+    pub fn dependents_of(&self, command: MenuCommand) -> Vec<MenuCommand> {
+        self.service_order()
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .filter(|&svc| svc != command && self.start_order(svc).contains(&command))
+            .collect()
+    }
+    fn visit(
+        &self,
+        command: MenuCommand,
+        path: &mut Vec<MenuCommand>,
+        order: &mut Vec<MenuCommand>,
+    ) -> Result<()> {
+        if order.contains(&command) {
+            return Ok(());
+        }
+        if path.contains(&command) {
+            path.push(command);
+            let cycle = path
+                .iter()
+                .map(|c| format!("{:?}", c))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("dependency cycle: {}", cycle),
+            ));
+        }
+        let info = self.find(command).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("unknown dependency: {:?}", command),
+            )
+        })?;
+        path.push(command);
+        for dep in &info.depends_on {
+            if !dep.is_service() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{:?} depends on {:?} which is not a service", command, dep),
+                ));
+            }
+            self.visit(*dep, path, order)?;
+        }
+        path.pop();
+        order.push(command);
+        Ok(())
     }
     pub fn _to_string(&self) -> String {
         serde_json::to_string_pretty(self).unwrap_or_else(|_| "Failed serializing...".to_string())
     }
+    /// replaces the `args` of the entry `key` in tool.json, the rest of the file stays as written
+    pub fn save_args(key: &str, args: &[String]) -> Result<()> {
+        let text = fs::read_to_string(FILEPATH)?;
+        write_config(&with_args(&text, key, args)?)
+    }
+    /// replaces the `commands` of tool.json, the other sections stay as written
+    pub fn save_commands(commands: &[CmdInfo]) -> Result<()> {
+        let text = fs::read_to_string(FILEPATH)?;
+        write_config(&with_commands(&text, commands)?)
+    }
     pub fn read() -> Result<Config> {
         let file = File::open(FILEPATH)?;
         let json: Config = serde_json::from_reader(file)?;
@@ -83,6 +938,8 @@ impl Config {
 {
   "fps": 30.0,
   "limit": 20,
+  "on_exit": "ask",
+  "max_jobs": 2,
   "commands": [
     {
       "name": "run",
@@ -90,6 +947,36 @@ impl Config {
       "exe_path": "C:/Users/jmkl/.conda/envs/comfyui/python.exe",
       "work_dir": "C:/Comfyui-2024",
       "command": "ComfyRun",
+      "ready_pattern": "To see the GUI go to",
+      "hooks": {
+        "pre_start": {
+          "exe_path": "pwsh",
+          "args": ["-NoProfile", "-Command", "Remove-Item temp/* -Recurse -Force -ErrorAction SilentlyContinue"],
+          "timeout": 60
+        }
+      },
+      "triggers": [
+        { "pattern": "To see the GUI go to: (http\\S+)", "action": { "toast": "ComfyUI is up at $1" } },
+        { "pattern": "CUDA out of memory", "action": "flash", "cooldown": 60 },
+        { "pattern": "CUDA out of memory", "action": { "toast": "ComfyUI ran out of VRAM, restarting" }, "cooldown": 60 },
+        { "pattern": "CUDA out of memory", "action": "restart", "cooldown": 60 }
+      ],
+      "pip_snapshots": true,
+      "flags": [
+        { "name": "low vram", "flag": "--lowvram" },
+        { "name": "cors header", "flag": "--enable-cors-header" },
+        {
+          "name": "preview method",
+          "flag": "--preview-method",
+          "kind": { "choice": ["auto", "latent2rgb", "taesd", "none"] }
+        },
+        { "name": "front end", "flag": "--front-end-version", "kind": "text" }
+      ],
+      "thresholds": {
+        "rss_mb": 16384,
+        "sustain": 10,
+        "action": "warn"
+      },
       "args": [
         "main.py",
         "--enable-cors-header",
@@ -102,12 +989,45 @@ impl Config {
     },
     {
       "name": "update",
-      "desc": "update comfyui",
-      "exe_path": "git",
+      "desc": "stop comfyui, pull, install its requirements and start it again",
       "work_dir": "C:/Comfyui-2024",
       "command": "ComfyUpdate",
-      "args": [
-        "pull"
+      "timeout": 900,
+      "retry": { "count": 2, "delay": 30 },
+      "params": [
+        {
+          "name": "branch",
+          "prompt": "branch to update",
+          "default": "master",
+          "pattern": "[A-Za-z0-9._/-]+"
+        }
+      ],
+      "steps": [
+        {
+          "name": "stop server",
+          "service": "ComfyRun",
+          "action": "stop"
+        },
+        {
+          "name": "checkout branch",
+          "exe_path": "git",
+          "args": ["checkout", "{branch}"]
+        },
+        {
+          "name": "git update",
+          "git": "update",
+          "dirty": "refuse"
+        },
+        {
+          "name": "install requirements",
+          "exe_path": "C:/Users/jmkl/.conda/envs/comfyui/python.exe",
+          "args": ["-m", "pip", "install", "-r", "requirements.txt"]
+        },
+        {
+          "name": "start server",
+          "service": "ComfyRun",
+          "action": "start"
+        }
       ]
     },
     {
@@ -115,6 +1035,26 @@ impl Config {
       "desc": "kill process by save PID",
       "command": "ComfyKill"
     },
+    {
+      "name": "restart",
+      "desc": "stop comfyui, wait for it to exit and start it again",
+      "command": "ComfyRestart"
+    },
+    {
+      "name": "pause",
+      "desc": "freeze comfyui and its children, again to resume",
+      "command": "ComfyPause"
+    },
+    {
+      "name": "signal",
+      "desc": "send a signal to comfyui",
+      "command": "ComfySignal"
+    },
+    {
+      "name": "nodes",
+      "desc": "custom nodes with their upstream status, update one or all",
+      "command": "ComfyNodes"
+    },
     {
       "name": "start",
       "desc": "start deno server for the wsm-mandala.vercel.app updating database",
@@ -124,13 +1064,51 @@ impl Config {
       "args": [
         "run",
         "dev"
-      ]
+      ],
+      "depends_on": ["ComfyRun"],
+      "hooks": {
+        "post_stop": {
+          "exe_path": "pwsh",
+          "args": ["-NoProfile", "-Command", "Copy-Item data/db.sqlite \"backups/db-$(Get-Date -Format yyyyMMdd-HHmmss).sqlite\""],
+          "timeout": 120
+        }
+      }
     },
     {
       "name": "stop",
       "desc": "stop deno server",
       "command": "CronKill"
     },
+    {
+      "name": "restart",
+      "desc": "stop the deno server, wait for it to exit and start it again",
+      "command": "CronRestart"
+    },
+    {
+      "name": "pause",
+      "desc": "freeze the deno server, again to resume",
+      "command": "CronPause"
+    },
+    {
+      "name": "signal",
+      "desc": "send a signal to the deno server",
+      "command": "CronSignal"
+    },
+    {
+      "name": "start all",
+      "desc": "start every service in dependency order",
+      "command": "StartAll"
+    },
+    {
+      "name": "stop all",
+      "desc": "stop every service, dependents first",
+      "command": "StopAll"
+    },
+    {
+      "name": "packages",
+      "desc": "python package snapshots taken around updates, with their differences",
+      "command": "Packages"
+    },
     {
       "name": "config",
       "desc": "show the current config",
@@ -146,10 +1124,28 @@ impl Config {
       "desc": "quit the application",
       "command": "Exit"
     }
-  ]
+  ],
+  "quick_actions": [
+    {
+      "key": "t",
+      "name": "list task",
+      "command": "tasklist | rg \"deno*|python*\"",
+      "shell": true
+    },
+    {
+      "key": "k",
+      "name": "kill all",
+      "command": "taskkill /f /im deno* && taskkill /f /im python*",
+      "shell": true
+    }
+  ],
+  "custom_nodes": {
+    "dir": "C:/Comfyui-2024/custom_nodes",
+    "parallel": 4
+  }
 }
         "#;
-        let config = serde_json::from_str::<Config>(&cmds).unwrap();
+        let config = serde_json::from_str::<Config>(cmds).unwrap();
         let file = File::create(FILEPATH).expect("Cannot create a file");
         let mut writer = BufWriter::new(file);
         serde_json::to_writer_pretty(&mut writer, &config).expect("Cannot write a file");
@@ -157,3 +1153,341 @@ impl Config {
         config
     }
 }
+
+/// writes a temp file next to tool.json and renames it over, the old file is kept as a `.bak`
+fn write_config(text: &str) -> Result<()> {
+    let tmp = format!("{}.tmp", FILEPATH);
+    let mut file = File::create(&tmp)?;
+    file.write_all(text.as_bytes())?;
+    file.sync_all()?;
+    if fs::exists(FILEPATH)? {
+        fs::copy(FILEPATH, format!("{}.bak", FILEPATH))?;
+    }
+    fs::rename(&tmp, FILEPATH)
+}
+
+/// the commands array of tool.json as written
+#[derive(Deserialize)]
+struct RawConfig<'a> {
+    #[serde(borrow)]
+    commands: &'a RawValue,
+}
+
+/// `text` with the commands array pretty printed from `commands`, every other byte is kept
+fn with_commands(text: &str, commands: &[CmdInfo]) -> Result<String> {
+    let raw: RawConfig = serde_json::from_str(text)?;
+    let at = raw.commands.get().as_ptr() as usize - text.as_ptr() as usize;
+    let line = &text[text[..at].rfind('\n').map_or(0, |i| i + 1)..at];
+    let indent = line
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect::<String>();
+    let array = serde_json::to_string_pretty(commands)?.replace('\n', &format!("\n{}", indent));
+    Ok(format!(
+        "{}{}{}",
+        &text[..at],
+        array,
+        &text[at + raw.commands.get().len()..]
+    ))
+}
+
+/// the commands of tool.json as the text they were written with
+#[derive(Deserialize)]
+struct RawCommands<'a> {
+    #[serde(borrow)]
+    commands: Vec<&'a RawValue>,
+}
+
+/// what tells an entry of tool.json apart
+#[derive(Deserialize)]
+struct RawEntry {
+    name: String,
+    command: MenuCommand,
+}
+
+/// `text` with the args array of the entry `key` rewritten, every other byte is kept
+fn with_args(text: &str, key: &str, args: &[String]) -> Result<String> {
+    let raw: RawCommands = serde_json::from_str(text)?;
+    let mut fields = None;
+    for entry in raw.commands {
+        let named: RawEntry = serde_json::from_str(entry.get())?;
+        if entry_key(named.command, &named.name) == key {
+            fields = Some(serde_json::from_str::<BTreeMap<String, &RawValue>>(
+                entry.get(),
+            )?);
+            break;
+        }
+    }
+    let fields = fields.ok_or_else(|| {
+        Error::new(
+            ErrorKind::NotFound,
+            format!("{} is not in {}", key, FILEPATH),
+        )
+    })?;
+    // the raw values borrow from `text`, their addresses give the offsets
+    let offset = |part: &str| part.as_ptr() as usize - text.as_ptr() as usize;
+    let indent = |at: usize| {
+        let line = &text[text[..at].rfind('\n').map_or(0, |i| i + 1)..at];
+        line.chars()
+            .take_while(|c| c.is_whitespace())
+            .collect::<String>()
+    };
+    let quoted = args
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let (at, len, indent, multiline) = match fields.get("args") {
+        Some(old) => {
+            let at = offset(old.get());
+            (at, old.get().len(), indent(at), old.get().contains('\n'))
+        }
+        None => {
+            // a new field right after the command value, which the entry was found by
+            let value = fields["command"].get();
+            let end = offset(value) + value.len();
+            let field = format!(",\n{}\"args\": []", indent(end));
+            let text = format!("{}{}{}", &text[..end], field, &text[end..]);
+            return with_args(&text, key, args);
+        }
+    };
+    let array = if multiline && !quoted.is_empty() {
+        let items = quoted
+            .iter()
+            .map(|q| format!("{}  {}", indent, q))
+            .collect::<Vec<_>>();
+        format!("[\n{}\n{}]", items.join(",\n"), indent)
+    } else {
+        format!("[{}]", quoted.join(", "))
+    };
+    Ok(format!("{}{}{}", &text[..at], array, &text[at + len..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+  "commands": [
+    {
+      "name": "run",
+      "command": "ComfyRun",
+      "args": ["--port", "8188"],
+      "limits": { "nice": 5 }
+    },
+    {
+      "name": "update",
+      "command": "ComfyUpdate",
+      "retry": { "attempts": 2 }
+    },
+    {
+      "name": "backup",
+      "command": "Custom",
+      "exe_path": "restic",
+      "args": ["backup"]
+    },
+    {
+      "name": "prune",
+      "command": "Custom",
+      "exe_path": "restic",
+      "args": ["forget"]
+    },
+    {
+      "name": "quit",
+      "command": "Exit"
+    }
+  ]
+}"#;
+
+    fn args_of(text: &str, key: &str) -> Option<Vec<String>> {
+        let raw: RawCommands = serde_json::from_str(text).unwrap();
+        raw.commands.iter().find_map(|entry| {
+            let named: RawEntry = serde_json::from_str(entry.get()).unwrap();
+            let value: serde_json::Value = serde_json::from_str(entry.get()).unwrap();
+            (entry_key(named.command, &named.name) == key)
+                .then(|| serde_json::from_value(value["args"].clone()).ok())
+                .flatten()
+        })
+    }
+
+    #[test]
+    fn with_args_replaces_existing_args() {
+        let args = vec!["--listen".to_string()];
+        let text = with_args(CONFIG, "ComfyRun/run", &args).unwrap();
+        assert_eq!(args_of(&text, "ComfyRun/run"), Some(args));
+        assert!(text.contains(r#""args": ["--listen"],"#));
+        assert!(text.contains(r#""limits": { "nice": 5 }"#));
+    }
+
+    #[test]
+    fn with_args_adds_args_after_command() {
+        let args = vec!["a b".to_string(), "c".to_string()];
+        let text = with_args(CONFIG, "ComfyUpdate/update", &args).unwrap();
+        assert_eq!(args_of(&text, "ComfyUpdate/update"), Some(args.clone()));
+        assert!(text.contains(
+            "      \"command\": \"ComfyUpdate\",\n      \"args\": [\"a b\", \"c\"],\n      \"retry\""
+        ));
+        let text = with_args(CONFIG, "Exit/quit", &args).unwrap();
+        assert_eq!(args_of(&text, "Exit/quit"), Some(args));
+        assert!(text.contains("\"Exit\",\n      \"args\": [\"a b\", \"c\"]\n    }"));
+    }
+
+    #[test]
+    fn with_args_tells_custom_entries_apart() {
+        let args = vec!["prune".to_string()];
+        let text = with_args(CONFIG, "Custom/prune", &args).unwrap();
+        assert_eq!(args_of(&text, "Custom/prune"), Some(args));
+        assert_eq!(
+            args_of(&text, "Custom/backup"),
+            Some(vec!["backup".to_string()])
+        );
+    }
+
+    #[test]
+    fn with_args_needs_the_command() {
+        let text = with_args(CONFIG, "CronRun/start", &[]);
+        assert!(text.is_err());
+    }
+
+    fn config(commands: serde_json::Value) -> Config {
+        serde_json::from_value(serde_json::json!({
+            "fps": 10.0,
+            "limit": 100,
+            "commands": commands
+        }))
+        .unwrap()
+    }
+
+    fn order_error(config: &Config) -> String {
+        config.service_order().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn services_start_after_their_dependencies() {
+        let config = config(serde_json::json!([
+            { "name": "cron", "desc": "", "command": "CronRun", "depends_on": ["ComfyRun"] },
+            { "name": "run", "desc": "", "command": "ComfyRun" },
+            { "name": "update", "desc": "", "command": "ComfyUpdate" }
+        ]));
+        let both = vec![MenuCommand::ComfyRun, MenuCommand::CronRun];
+        assert_eq!(config.service_order().unwrap(), both);
+        assert_eq!(config.start_order(MenuCommand::CronRun), both);
+        assert_eq!(
+            config.start_order(MenuCommand::ComfyRun),
+            [MenuCommand::ComfyRun]
+        );
+        assert_eq!(
+            config.dependents_of(MenuCommand::ComfyRun),
+            [MenuCommand::CronRun]
+        );
+    }
+
+    #[test]
+    fn dependency_cycles_are_refused() {
+        let cycle = config(serde_json::json!([
+            { "name": "run", "desc": "", "command": "ComfyRun", "depends_on": ["CronRun"] },
+            { "name": "cron", "desc": "", "command": "CronRun", "depends_on": ["ComfyRun"] }
+        ]));
+        assert_eq!(
+            order_error(&cycle),
+            "dependency cycle: ComfyRun -> CronRun -> ComfyRun"
+        );
+        let itself = config(serde_json::json!([
+            { "name": "run", "desc": "", "command": "ComfyRun", "depends_on": ["ComfyRun"] }
+        ]));
+        assert_eq!(
+            order_error(&itself),
+            "dependency cycle: ComfyRun -> ComfyRun"
+        );
+    }
+
+    #[test]
+    fn dependencies_must_be_configured_services() {
+        let command = config(serde_json::json!([
+            { "name": "run", "desc": "", "command": "ComfyRun", "depends_on": ["ComfyUpdate"] },
+            { "name": "update", "desc": "", "command": "ComfyUpdate" }
+        ]));
+        assert_eq!(
+            order_error(&command),
+            "ComfyRun depends on ComfyUpdate which is not a service"
+        );
+        let missing = config(serde_json::json!([
+            { "name": "cron", "desc": "", "command": "CronRun", "depends_on": ["ComfyRun"] }
+        ]));
+        assert_eq!(order_error(&missing), "unknown dependency: ComfyRun");
+    }
+
+    fn step(json: &str) -> Step {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn steps_tell_their_kind_by_their_fields() {
+        let restart = step(r#"{ "service": "ComfyRun", "action": "restart" }"#);
+        assert_eq!(
+            restart.kind,
+            StepKind::Service {
+                service: MenuCommand::ComfyRun,
+                action: ServiceAction::Restart
+            }
+        );
+        assert_eq!(restart.label(), "Restart ComfyRun");
+        let update = step(r#"{ "git": "update" }"#);
+        assert_eq!(
+            update.kind,
+            StepKind::Git {
+                git: GitAction::Update,
+                dirty: DirtyPolicy::Refuse,
+                work_dir: String::new()
+            }
+        );
+        assert_eq!(update.label(), "git update");
+        let stash = step(r#"{ "git": "update", "dirty": "stash", "work_dir": "app" }"#);
+        assert!(matches!(
+            stash.kind,
+            StepKind::Git { dirty: DirtyPolicy::Stash, ref work_dir, .. } if work_dir == "app"
+        ));
+        let pull = step(
+            r#"{ "name": "pull", "exe_path": "git", "args": ["pull"], "continue_on_error": true }"#,
+        );
+        assert!(pull.continue_on_error);
+        assert_eq!(pull.label(), "pull");
+        assert!(matches!(pull.kind, StepKind::Run { ref args, .. } if args == &["pull"]));
+        assert!(serde_json::from_str::<Step>(r#"{ "service": "ComfyRun" }"#).is_err());
+        assert!(serde_json::from_str::<Step>(r#"{ "git": "pull" }"#).is_err());
+    }
+
+    #[test]
+    fn steps_are_checked() {
+        let steps = |steps: serde_json::Value| {
+            config(serde_json::json!([
+                { "name": "run", "desc": "", "command": "ComfyRun" },
+                { "name": "update", "desc": "", "command": "ComfyUpdate", "steps": steps }
+            ]))
+            .check_steps()
+            .map_err(|err| err.to_string())
+        };
+        let fine = serde_json::json!([
+            { "service": "ComfyRun", "action": "stop" },
+            { "git": "update" },
+            { "exe_path": "pip", "args": ["install", "-r", "requirements.txt"] }
+        ]);
+        assert!(steps(fine).is_ok());
+        assert_eq!(
+            steps(serde_json::json!([{ "service": "CronRun", "action": "start" }])),
+            Err(
+                "step \"Start CronRun\" of ComfyUpdate needs a configured service, not CronRun"
+                    .to_string()
+            )
+        );
+        assert!(
+            steps(serde_json::json!([{ "service": "ComfyUpdate", "action": "start" }])).is_err()
+        );
+        let service = config(serde_json::json!([
+            { "name": "run", "desc": "", "command": "ComfyRun", "steps": [{ "git": "update" }] }
+        ]));
+        assert_eq!(
+            service.check_steps().unwrap_err().to_string(),
+            "ComfyRun is a service and cannot have steps"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// next to tool.json while the ui runs, `port token` of its control listener
+const FILE: &str = "tool.control";
+/// how long the command line waits for the ui to answer
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// what another `tool` process asks the running ui to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    /// a menu command name like `ComfyRun` or the name of its menu entry
+    Restart(String),
+}
+
+impl Request {
+    fn line(&self) -> String {
+        match self {
+            Request::Restart(service) => format!("restart {}", service),
+        }
+    }
+    fn parse(line: &str) -> Option<Request> {
+        let (verb, rest) = line.trim().split_once(' ')?;
+        match verb {
+            "restart" => Some(Request::Restart(rest.trim().to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// listens on localhost for requests from the command line, only a client that read the
+/// token from `tool.control` is served
+pub struct Listener {
+    listener: TcpListener,
+    token: String,
+}
+
+impl Listener {
+    pub async fn bind() -> io::Result<Listener> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let token = token()?;
+        let port = listener.local_addr()?.port();
+        write_private(&format!("{} {}\n", port, token))?;
+        Ok(Listener { listener, token })
+    }
+    /// hands every request with a valid token to `requests`, each connection is read in
+    /// its own task so a client that connects and says nothing holds up no one
+    pub async fn serve(self, requests: UnboundedSender<(Request, Reply)>) {
+        while let Ok((stream, _)) = self.listener.accept().await {
+            if requests.is_closed() {
+                break;
+            }
+            let (token, requests) = (self.token.clone(), requests.clone());
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut line = String::new();
+                let mut read = tokio::io::BufReader::new(read);
+                let read = read.read_line(&mut line);
+                if tokio::time::timeout(TIMEOUT, read).await.is_err() {
+                    return;
+                }
+                let request = line.trim().strip_prefix(&token).and_then(Request::parse);
+                match request {
+                    Some(request) => {
+                        let _ = requests.send((request, Reply { stream: write }));
+                    }
+                    None => {
+                        let _ = write.write_all(b"error bad request\n").await;
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// 16 random bytes in hex, from the system's random source
+#[cfg(unix)]
+fn token() -> io::Result<String> {
+    use std::io::Read;
+    let mut bytes = [0u8; 16];
+    fs::File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+/// the keys of `RandomState` come from the system's random source on windows
+#[cfg(not(unix))]
+fn token() -> io::Result<String> {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    let half = || RandomState::new().build_hasher().finish();
+    Ok(format!("{:016x}{:016x}", half(), half()))
+}
+
+/// writes `tool.control` readable by the user only, a leftover file is replaced
+fn write_private(text: &str) -> io::Result<()> {
+    let _ = fs::remove_file(FILE);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(FILE)?.write_all(text.as_bytes())
+}
+
+/// the open connection of a request
+pub struct Reply {
+    stream: tokio::net::tcp::OwnedWriteHalf,
+}
+
+impl Reply {
+    pub async fn send(mut self, ok: bool, text: &str) {
+        let status = if ok { "ok" } else { "error" };
+        let line = format!("{} {}\n", status, text);
+        let _ = self.stream.write_all(line.as_bytes()).await;
+    }
+}
+
+/// removes `tool.control` when the ui quits
+pub fn remove() {
+    let _ = fs::remove_file(FILE);
+}
+
+/// sends `request` to the ui running in this folder, its reply text or why it failed
+pub fn send(request: &Request) -> Result<String, String> {
+    let text = fs::read_to_string(FILE).map_err(|_| "the tool is not running here".to_string())?;
+    let (port, token) = text
+        .trim()
+        .split_once(' ')
+        .ok_or_else(|| format!("{} is damaged", FILE))?;
+    let addr = format!("127.0.0.1:{}", port)
+        .parse()
+        .map_err(|_| format!("{} is damaged", FILE))?;
+    let exchange = || -> io::Result<String> {
+        let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.write_all(format!("{} {}\n", token, request.line()).as_bytes())?;
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        Ok(reply)
+    };
+    let reply = exchange().map_err(|err| format!("no answer from the tool :{}", err))?;
+    match reply.trim().split_once(' ') {
+        Some(("ok", text)) => Ok(text.to_string()),
+        Some((_, text)) => Err(text.to_string()),
+        None => Err("no answer from the tool".to_string()),
+    }
+}
@@ -0,0 +1,57 @@
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// restores the terminal and writes a crash report when the main loop panics,
+/// panics inside spawned tasks only get a report, the supervisor shows them in the debug console
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let report = write_report(info);
+        if tokio::task::try_id().is_some() {
+            return;
+        }
+        ratatui::restore();
+        previous(info);
+        if let Some(path) = report {
+            eprintln!("crash report written to {}", path);
+        }
+    }));
+}
+
+fn write_report(info: &PanicHookInfo) -> Option<String> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let path = format!("crash-{}.log", secs);
+    let location = info
+        .location()
+        .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+        .unwrap_or_else(|| "unknown".to_string());
+    let report = format!(
+        "tool {} crashed at {}\nthread   : {}\ntask     : {:?}\nlocation : {}\nmessage  : {}\n\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        secs,
+        thread::current().name().unwrap_or("unnamed"),
+        tokio::task::try_id(),
+        location,
+        payload_message(info.payload()),
+        Backtrace::force_capture(),
+    );
+    fs::write(&path, report).ok().map(|_| path)
+}
+
+/// the `&str` or `String` a panic was raised with
+pub fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
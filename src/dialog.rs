@@ -0,0 +1,75 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear, Padding, Paragraph, Widget, Wrap};
+
+/// modal popup with a message and a set of single key choices, Esc always cancels
+#[derive(Debug, Clone)]
+pub struct Dialog<A> {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub options: Vec<(char, String, A)>,
+}
+
+impl<A: Clone> Dialog<A> {
+    pub fn new(title: &str, lines: Vec<String>) -> Self {
+        Self {
+            title: title.to_string(),
+            lines,
+            options: Vec::new(),
+        }
+    }
+    pub fn option(mut self, key: char, label: &str, action: A) -> Self {
+        self.options.push((key, label.to_string(), action));
+        self
+    }
+    pub fn action(&self, key: char) -> Option<A> {
+        self.options
+            .iter()
+            .find(|(k, _, _)| *k == key)
+            .map(|(_, _, action)| action.clone())
+    }
+}
+
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+impl<A> Widget for &Dialog<A> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut lines: Vec<Line> = self.lines.iter().map(|l| Line::raw(l.clone())).collect();
+        lines.push(Line::raw(""));
+        for (key, label, _) in &self.options {
+            lines.push(Line::from(vec![
+                format!(" {} ", key).bold().fg(Color::Red),
+                format!(" {}", label).into(),
+            ]));
+        }
+        lines.push(Line::from(vec![
+            " Esc ".bold().fg(Color::Red),
+            " cancel".into(),
+        ]));
+
+        let width = area.width.min(60);
+        let height = (lines.len() as u16 + 4).min(area.height);
+        let popup = centered(area, width, height);
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .block(
+                Block::new()
+                    .title(self.title.clone())
+                    .borders(Borders::ALL)
+                    .border_style(Color::Red)
+                    .padding(Padding::uniform(1)),
+            )
+            .wrap(Wrap { trim: true })
+            .render(popup, buf);
+    }
+}
@@ -0,0 +1,309 @@
+use crate::command::{CmdInfo, Hooks, Limits, MenuCommand, StdinMode};
+use crate::form::FormEvent;
+use crate::input::{InputEvent, LineInput};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget};
+use ratatui::widgets::{Widget, Wrap};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Name,
+    Desc,
+    Command,
+    ExePath,
+    WorkDir,
+    Args,
+}
+
+const FIELDS: [Field; 6] = [
+    Field::Name,
+    Field::Desc,
+    Field::Command,
+    Field::ExePath,
+    Field::WorkDir,
+    Field::Args,
+];
+
+impl Field {
+    fn label(&self) -> &'static str {
+        match self {
+            Field::Name => "name",
+            Field::Desc => "description",
+            Field::Command => "command",
+            Field::ExePath => "program",
+            Field::WorkDir => "folder",
+            Field::Args => "arguments",
+        }
+    }
+}
+
+/// the basic fields of one menu entry, everything else of the entry is kept as it was
+#[derive(Debug, Clone)]
+pub struct CommandEditor {
+    /// position in the menu, `None` until a new entry is saved
+    pub index: Option<usize>,
+    /// where a new entry goes
+    pub insert_at: usize,
+    original: CmdInfo,
+    name: String,
+    /// newlines shown as `\n` so it fits on one line
+    desc: String,
+    command: MenuCommand,
+    exe_path: String,
+    work_dir: String,
+    /// space separated, double quotes keep spaces together
+    args: String,
+    selected: usize,
+    editing: Option<LineInput>,
+    pub changed: bool,
+    /// why the last save was refused
+    pub error: Option<String>,
+}
+
+impl CommandEditor {
+    pub fn edit(cmd: &CmdInfo, index: usize) -> Self {
+        CommandEditor {
+            index: Some(index),
+            insert_at: index,
+            original: cmd.clone(),
+            name: cmd.name.clone(),
+            desc: cmd.desc.replace('\n', "\\n"),
+            command: cmd.command,
+            exe_path: cmd.exe_path.clone(),
+            work_dir: cmd.work_dir.clone(),
+            args: join_args(&cmd.args),
+            selected: 0,
+            editing: None,
+            changed: false,
+            error: None,
+        }
+    }
+    /// an empty entry that goes in at `insert_at` once saved
+    pub fn new(insert_at: usize) -> Self {
+        let cmd = CmdInfo {
+            name: "new command".to_string(),
+            desc: String::new(),
+            command: MenuCommand::Custom,
+            exe_path: String::new(),
+            work_dir: String::new(),
+            args: Vec::new(),
+            depends_on: Vec::new(),
+            ready_pattern: String::new(),
+            autostart: false,
+            autostart_delay: 0,
+            stdin: StdinMode::default(),
+            pty: false,
+            thresholds: None,
+            limits: Limits::default(),
+            steps: Vec::new(),
+            pip_snapshots: false,
+            flags: Vec::new(),
+            params: Vec::new(),
+            timeout: None,
+            retry: None,
+            hooks: Hooks::default(),
+            triggers: Vec::new(),
+            panel: None,
+        };
+        let mut editor = CommandEditor::edit(&cmd, insert_at);
+        editor.index = None;
+        editor.changed = true;
+        editor
+    }
+    pub fn name(&self) -> &str {
+        self.name.trim()
+    }
+    /// the entry as it will be saved
+    pub fn cmd(&self) -> CmdInfo {
+        CmdInfo {
+            name: self.name.clone(),
+            desc: self.desc.replace("\\n", "\n"),
+            command: self.command,
+            exe_path: self.exe_path.trim().to_string(),
+            work_dir: self.work_dir.trim().to_string(),
+            args: split_args(&self.args),
+            ..self.original.clone()
+        }
+    }
+    pub fn editing(&self) -> bool {
+        self.editing.is_some()
+    }
+    fn value(&self, field: Field) -> String {
+        match field {
+            Field::Name => self.name.clone(),
+            Field::Desc => self.desc.clone(),
+            Field::Command => format!("{:?}", self.command),
+            Field::ExePath => self.exe_path.clone(),
+            Field::WorkDir => self.work_dir.clone(),
+            Field::Args => self.args.clone(),
+        }
+    }
+    fn cycle(&mut self, step: isize) {
+        let all = MenuCommand::ALL;
+        let position = all.iter().position(|c| *c == self.command).unwrap_or(0) as isize;
+        self.command = all[(position + step).rem_euclid(all.len() as isize) as usize];
+        self.changed = true;
+    }
+    pub fn handle_key(&mut self, key: &KeyEvent) -> FormEvent {
+        let field = FIELDS[self.selected];
+        if let Some(input) = self.editing.as_mut() {
+            match input.handle_key(key) {
+                InputEvent::Submit(value) => {
+                    self.editing = None;
+                    match field {
+                        Field::Name => self.name = value,
+                        Field::Desc => self.desc = value,
+                        Field::ExePath => self.exe_path = value,
+                        Field::WorkDir => self.work_dir = value,
+                        Field::Args => self.args = value,
+                        Field::Command => {}
+                    }
+                    self.changed = true;
+                    self.error = None;
+                }
+                InputEvent::Cancel => self.editing = None,
+                InputEvent::Eof | InputEvent::Edited => {}
+            }
+            return FormEvent::Edited;
+        }
+        match (key.code, field) {
+            (KeyCode::Down, _) => self.selected = (self.selected + 1).min(FIELDS.len() - 1),
+            (KeyCode::Up, _) => self.selected = self.selected.saturating_sub(1),
+            (KeyCode::Right | KeyCode::Enter | KeyCode::Char(' '), Field::Command) => self.cycle(1),
+            (KeyCode::Left, Field::Command) => self.cycle(-1),
+            (KeyCode::Enter, field) => {
+                let mut input = LineInput::default();
+                input.buffer = self.value(field);
+                self.editing = Some(input);
+            }
+            (KeyCode::Char('s'), _) => return FormEvent::Save,
+            (KeyCode::Esc, _) => return FormEvent::Close,
+            _ => {}
+        }
+        FormEvent::Edited
+    }
+    pub fn render(&self, block: Block, area: Rect, buf: &mut Buffer) {
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [list_area, notes_area, input_area] = Layout::vertical([
+            Constraint::Length(FIELDS.len() as u16),
+            Constraint::Min(1),
+            Constraint::Length(if self.editing.is_some() { 3 } else { 0 }),
+        ])
+        .areas(inner);
+        let items = FIELDS
+            .iter()
+            .map(|&field| {
+                let value = self.value(field);
+                let value = if field == Field::Command {
+                    Span::raw(format!("< {} >", value)).green()
+                } else {
+                    Span::raw(value)
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:<12} ", field.label()), Style::new().bold()),
+                    value,
+                ]))
+            })
+            .collect::<Vec<_>>();
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        let list = List::new(items).highlight_style(
+            Style::new()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+        StatefulWidget::render(list, list_area, buf, &mut state);
+        let mut notes = Vec::new();
+        if let Some(error) = &self.error {
+            notes.push(Line::raw(error.clone()).red());
+        }
+        let kept = self.kept();
+        if !kept.is_empty() {
+            notes.push(
+                Line::raw(format!("kept as written in tool.json :{}", kept.join(", "))).dark_gray(),
+            );
+        }
+        if self.changed {
+            notes.push(Line::raw("not saved").yellow());
+        }
+        Paragraph::new(notes)
+            .block(Block::new().borders(Borders::TOP))
+            .wrap(Wrap { trim: true })
+            .render(notes_area, buf);
+        if let Some(input) = &self.editing {
+            input.render(FIELDS[self.selected].label(), input_area, buf);
+        }
+    }
+    /// settings of the entry the editor does not show
+    fn kept(&self) -> Vec<&'static str> {
+        let cmd = &self.original;
+        [
+            ("steps", !cmd.steps.is_empty()),
+            ("params", !cmd.params.is_empty()),
+            ("flags", !cmd.flags.is_empty()),
+            ("depends_on", !cmd.depends_on.is_empty()),
+            ("ready_pattern", !cmd.ready_pattern.is_empty()),
+            ("autostart", cmd.autostart),
+            ("pty", cmd.pty),
+            ("thresholds", cmd.thresholds.is_some()),
+            ("limits", !cmd.limits.is_empty()),
+            ("pip_snapshots", cmd.pip_snapshots),
+            ("timeout", cmd.timeout.is_some()),
+            ("retry", cmd.retry.is_some()),
+            ("hooks", !cmd.hooks.is_empty()),
+            ("triggers", !cmd.triggers.is_empty()),
+        ]
+        .into_iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| name)
+        .collect()
+    }
+}
+
+/// splits on whitespace, double quotes keep spaces together and are dropped
+pub fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut started = false;
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    args.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        args.push(current);
+    }
+    args
+}
+
+/// the reverse of `split_args`, arguments with spaces get quoted
+pub fn join_args(args: &[String]) -> String {
+    args.iter()
+        .map(|a| {
+            if a.is_empty() || a.contains(char::is_whitespace) {
+                format!("\"{}\"", a)
+            } else {
+                a.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
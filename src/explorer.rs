@@ -0,0 +1,280 @@
+use crate::input::LineInput;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::widgets::{Block, Row, StatefulWidget, Table, TableState};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, Signal, System, UpdateKind};
+
+const REFRESH: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub struct ProcRow {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub name: String,
+    pub cpu: f32,
+    pub rss: u64,
+    pub uptime: u64,
+    pub cmd: String,
+}
+
+/// table of every process on the machine, sysinfo reads /proc on linux and platform APIs elsewhere
+#[derive(Debug, Default)]
+pub struct Explorer {
+    system: System,
+    rows: Vec<ProcRow>,
+    state: TableState,
+    selected: Option<u32>,
+    pub filter: LineInput,
+    pub filtering: bool,
+    last_refresh: Option<Instant>,
+}
+
+impl Explorer {
+    pub fn refresh(&mut self) {
+        if self.last_refresh.is_some_and(|t| t.elapsed() < REFRESH) {
+            return;
+        }
+        self.last_refresh = Some(Instant::now());
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_cpu()
+                .with_memory()
+                .with_cmd(UpdateKind::OnlyIfNotSet),
+        );
+        self.rows = self
+            .system
+            .processes()
+            .values()
+            // on linux threads are listed next to their process
+            .filter(|p| p.thread_kind().is_none())
+            .map(|p| ProcRow {
+                pid: p.pid().as_u32(),
+                parent: p.parent().map(|pid| pid.as_u32()),
+                name: p.name().to_string_lossy().to_string(),
+                cpu: p.cpu_usage(),
+                rss: p.memory(),
+                uptime: p.run_time(),
+                cmd: p
+                    .cmd()
+                    .iter()
+                    .map(|a| a.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            })
+            .collect();
+        self.rows
+            .sort_by(|a, b| b.cpu.total_cmp(&a.cpu).then(a.pid.cmp(&b.pid)));
+    }
+    fn visible(&self) -> Vec<&ProcRow> {
+        let filter = self.filter.buffer.to_lowercase();
+        self.rows
+            .iter()
+            .filter(|r| {
+                filter.is_empty()
+                    || r.name.to_lowercase().contains(&filter)
+                    || r.cmd.to_lowercase().contains(&filter)
+            })
+            .collect()
+    }
+    pub fn selected(&self) -> Option<&ProcRow> {
+        let pid = self.selected?;
+        self.rows.iter().find(|r| r.pid == pid)
+    }
+    pub fn select(&mut self, step: isize) {
+        let visible = self.visible();
+        if visible.is_empty() {
+            return;
+        }
+        let current = self
+            .selected
+            .and_then(|pid| visible.iter().position(|r| r.pid == pid));
+        let next = match current {
+            Some(i) => i.saturating_add_signed(step).min(visible.len() - 1),
+            None => 0,
+        };
+        self.selected = Some(visible[next].pid);
+    }
+    pub fn render(&mut self, block: Block, managed: &[u32], area: Rect, buf: &mut Buffer) {
+        let managed = process_tree(self.rows.iter().map(|r| (r.pid, r.parent)), managed);
+        let visible = self.visible();
+        let index = self
+            .selected
+            .and_then(|pid| visible.iter().position(|r| r.pid == pid));
+        let rows = visible
+            .iter()
+            .map(|r| {
+                let row = Row::new(vec![
+                    r.pid.to_string(),
+                    r.parent.map(|p| p.to_string()).unwrap_or_default(),
+                    format!("{:.1}", r.cpu),
+                    format_bytes(r.rss),
+                    format_duration(r.uptime),
+                    if r.cmd.is_empty() {
+                        r.name.clone()
+                    } else {
+                        r.cmd.clone()
+                    },
+                ]);
+                if managed.contains(&r.pid) {
+                    row.fg(Color::Red)
+                } else {
+                    row
+                }
+            })
+            .collect::<Vec<_>>();
+        let count = rows.len();
+        let inner = block.inner(area);
+        ratatui::widgets::Widget::render(block, area, buf);
+        let [table_area, filter_area] = if self.filtering || !self.filter.buffer.is_empty() {
+            Layout::vertical([Constraint::Min(1), Constraint::Length(3)]).areas(inner)
+        } else {
+            [inner, Rect::default()]
+        };
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(6),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec!["PID", "PARENT", "CPU%", "RSS", "UPTIME", "COMMAND"])
+                .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(
+            Style::new()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+        self.state.select(index);
+        StatefulWidget::render(table, table_area, buf, &mut self.state);
+        if filter_area.height > 0 {
+            self.filter
+                .render(&format!("filter ({} shown)", count), filter_area, buf);
+        }
+    }
+}
+
+/// sends `signal` to `pid`, looked up again so the table does not have to be current
+pub fn send_signal(pid: u32, signal: Signal) -> Result<(), String> {
+    let mut system = System::new();
+    let pids = [Pid::from_u32(pid)];
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&pids),
+        true,
+        ProcessRefreshKind::nothing(),
+    );
+    let process = system
+        .process(pids[0])
+        .ok_or_else(|| format!("process {} is gone", pid))?;
+    match process.kill_with(signal) {
+        Some(true) => Ok(()),
+        Some(false) => Err(format!("failed to send {:?} to {}", signal, pid)),
+        None => Err(format!("{:?} is not supported on this platform", signal)),
+    }
+}
+
+/// sends `signal` to `root` and all of its descendants, returns how many processes got it
+pub fn signal_tree(root: u32, signal: Signal) -> Result<usize, String> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().without_tasks(),
+    );
+    let tree = process_tree(
+        system
+            .processes()
+            .values()
+            .filter(|p| p.thread_kind().is_none())
+            .map(|p| (p.pid().as_u32(), p.parent().map(|pid| pid.as_u32()))),
+        &[root],
+    );
+    let mut sent = 0;
+    for pid in tree {
+        let Some(process) = system.process(Pid::from_u32(pid)) else {
+            continue;
+        };
+        match process.kill_with(signal) {
+            Some(true) => sent += 1,
+            Some(false) => {}
+            None => return Err(format!("{:?} is not supported on this platform", signal)),
+        }
+    }
+    if sent == 0 {
+        return Err(format!("failed to send {:?} to {}", signal, root));
+    }
+    Ok(sent)
+}
+
+/// `roots` and all of their descendants, given `(pid, parent)` pairs
+pub fn process_tree(
+    links: impl Iterator<Item = (u32, Option<u32>)>,
+    roots: &[u32],
+) -> HashSet<u32> {
+    let links = links.collect::<Vec<_>>();
+    let mut tree: HashSet<u32> = roots.iter().copied().collect();
+    loop {
+        let before = tree.len();
+        for (pid, parent) in &links {
+            if parent.is_some_and(|p| tree.contains(&p)) {
+                tree.insert(*pid);
+            }
+        }
+        if tree.len() == before {
+            return tree;
+        }
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "K", "M", "G"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", value, UNITS[unit])
+}
+
+pub fn format_duration(secs: u64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m{:02}s", s / 60, s % 60),
+        s if s < 86400 => format!("{}h{:02}m", s / 3600, s % 3600 / 60),
+        s => format!("{}d{:02}h", s / 86400, s % 86400 / 3600),
+    }
+}
+
+/// `YYYY-MM-DD HH:MM` in UTC of a unix timestamp
+pub fn format_time(secs: u64) -> String {
+    // days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = (secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    let rem = secs % 86400;
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60
+    )
+}
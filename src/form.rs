@@ -0,0 +1,328 @@
+use crate::command::{CmdInfo, Flag, FlagKind};
+use crate::input::{InputEvent, LineInput};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget};
+use ratatui::widgets::{Widget, Wrap};
+
+/// what a key press in the form asks the app to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormEvent {
+    Save,
+    Close,
+    Edited,
+}
+
+/// a row of the form, flags first and then the remaining arguments in order
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Flag(usize),
+    Arg(usize),
+}
+
+/// `args` of one command as editable rows, known `flags` become toggles, choices and text fields
+#[derive(Debug, Clone)]
+pub struct ArgsForm {
+    /// the entry the args are saved to
+    pub key: String,
+    pub name: String,
+    exe_path: String,
+    flags: Vec<Flag>,
+    /// `None` when the flag is left out, the value is empty for toggles
+    values: Vec<Option<String>>,
+    /// where a flag stood in the loaded args, as the plain args before it and the order
+    /// it came in, flags turned on later go last
+    places: Vec<Option<(usize, usize)>>,
+    args: Vec<String>,
+    selected: usize,
+    /// the field being typed into
+    editing: Option<(Field, LineInput)>,
+    pub changed: bool,
+}
+
+impl ArgsForm {
+    /// a choice or text flag without a value after it counts as left out
+    pub fn new(cmd: &CmdInfo) -> Self {
+        let mut values = vec![None; cmd.flags.len()];
+        let mut places = vec![None; cmd.flags.len()];
+        let mut args = Vec::new();
+        let mut rest = cmd.args.iter().peekable();
+        let mut seen = 0;
+        while let Some(arg) = rest.next() {
+            let Some(i) = cmd.flags.iter().position(|f| &f.flag == arg) else {
+                args.push(arg.clone());
+                continue;
+            };
+            values[i] = match cmd.flags[i].kind {
+                FlagKind::Toggle => Some(String::new()),
+                _ => rest.next_if(|v| !v.starts_with("--")).cloned(),
+            };
+            places[i] = Some((args.len(), seen));
+            seen += 1;
+        }
+        ArgsForm {
+            key: cmd.key(),
+            name: cmd.name.trim().to_string(),
+            exe_path: cmd.exe_path.clone(),
+            flags: cmd.flags.clone(),
+            values,
+            places,
+            args,
+            selected: 0,
+            editing: None,
+            changed: false,
+        }
+    }
+    /// the arguments as they will be saved, flags stay where they were loaded
+    pub fn args(&self) -> Vec<String> {
+        let mut on = (0..self.flags.len())
+            .filter(|&i| self.values[i].is_some())
+            .collect::<Vec<_>>();
+        // a flag without a place sorts after every loaded one
+        on.sort_by_key(|&i| self.places[i].unwrap_or((usize::MAX, i)));
+        let mut on = on.into_iter().peekable();
+        let mut args = Vec::new();
+        let push_flag = |args: &mut Vec<String>, i: usize| {
+            args.push(self.flags[i].flag.clone());
+            if self.flags[i].kind != FlagKind::Toggle {
+                args.extend(self.values[i].clone());
+            }
+        };
+        for (position, arg) in self.args.iter().enumerate() {
+            while let Some(i) =
+                on.next_if(|&i| self.places[i].is_some_and(|(at, _)| at <= position))
+            {
+                push_flag(&mut args, i);
+            }
+            args.push(arg.clone());
+        }
+        for i in on {
+            push_flag(&mut args, i);
+        }
+        args
+    }
+    /// drops a plain argument, flags after it move up with the rest
+    fn remove_arg(&mut self, i: usize) {
+        self.args.remove(i);
+        for (at, _) in self.places.iter_mut().flatten() {
+            if *at > i {
+                *at -= 1;
+            }
+        }
+    }
+    pub fn editing(&self) -> bool {
+        self.editing.is_some()
+    }
+    fn fields(&self) -> Vec<Field> {
+        (0..self.flags.len())
+            .map(Field::Flag)
+            .chain((0..self.args.len()).map(Field::Arg))
+            .collect()
+    }
+    fn current(&self) -> Option<Field> {
+        self.fields().get(self.selected).copied()
+    }
+    fn edit(&mut self, field: Field) {
+        let mut input = LineInput::default();
+        input.buffer = match field {
+            Field::Flag(i) => self.values[i].clone().unwrap_or_default(),
+            Field::Arg(i) => self.args[i].clone(),
+        };
+        self.editing = Some((field, input));
+    }
+    /// an empty text flag is left out, an empty argument is removed
+    fn submit(&mut self, field: Field, value: String) {
+        match field {
+            Field::Flag(i) => self.values[i] = Some(value).filter(|v| !v.is_empty()),
+            Field::Arg(i) if value.is_empty() => self.remove_arg(i),
+            Field::Arg(i) => self.args[i] = value,
+        }
+        self.changed = true;
+    }
+    /// next (or previous) choice, going through "left out" between the last and the first
+    fn cycle(&mut self, i: usize, step: isize) {
+        let FlagKind::Choice(choices) = &self.flags[i].kind else {
+            return;
+        };
+        let slots = choices.len() as isize + 1;
+        let position = match &self.values[i] {
+            Some(value) => choices.iter().position(|c| c == value).map_or(0, |p| p + 1),
+            None => 0,
+        } as isize;
+        let next = (position + step).rem_euclid(slots) as usize;
+        self.values[i] = next.checked_sub(1).map(|c| choices[c].clone());
+        self.changed = true;
+    }
+    pub fn handle_key(&mut self, key: &KeyEvent) -> FormEvent {
+        if let Some((field, input)) = self.editing.as_mut() {
+            let field = *field;
+            match input.handle_key(key) {
+                InputEvent::Submit(value) => {
+                    self.editing = None;
+                    self.submit(field, value);
+                }
+                InputEvent::Cancel => {
+                    self.editing = None;
+                    // a freshly added argument that never got a value
+                    if let Field::Arg(i) = field {
+                        if self.args[i].is_empty() {
+                            self.remove_arg(i);
+                            self.selected =
+                                self.selected.min(self.fields().len().saturating_sub(1));
+                        }
+                    }
+                }
+                InputEvent::Eof | InputEvent::Edited => {}
+            }
+            return FormEvent::Edited;
+        }
+        let count = self.fields().len();
+        match (key.code, self.current()) {
+            (KeyCode::Down, _) => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            (KeyCode::Up, _) => self.selected = self.selected.saturating_sub(1),
+            (KeyCode::Char(' ') | KeyCode::Enter, Some(Field::Flag(i)))
+                if self.flags[i].kind == FlagKind::Toggle =>
+            {
+                self.values[i] = match self.values[i] {
+                    Some(_) => None,
+                    None => Some(String::new()),
+                };
+                self.changed = true;
+            }
+            (KeyCode::Right | KeyCode::Char(' '), Some(Field::Flag(i))) => self.cycle(i, 1),
+            (KeyCode::Left, Some(Field::Flag(i))) => self.cycle(i, -1),
+            (KeyCode::Enter, Some(Field::Flag(i))) => {
+                if self.flags[i].kind == FlagKind::Text {
+                    self.edit(Field::Flag(i));
+                } else {
+                    self.cycle(i, 1);
+                }
+            }
+            (KeyCode::Enter, Some(field)) => self.edit(field),
+            (KeyCode::Char('a'), _) => {
+                self.args.push(String::new());
+                self.selected = self.flags.len() + self.args.len() - 1;
+                self.edit(Field::Arg(self.args.len() - 1));
+            }
+            (KeyCode::Char('x') | KeyCode::Delete, Some(Field::Arg(i))) => {
+                self.remove_arg(i);
+                self.selected = self.selected.min(self.fields().len().saturating_sub(1));
+                self.changed = true;
+            }
+            (KeyCode::Char('s'), _) => return FormEvent::Save,
+            (KeyCode::Esc, _) => return FormEvent::Close,
+            _ => {}
+        }
+        FormEvent::Edited
+    }
+    pub fn render(&self, block: Block, area: Rect, buf: &mut Buffer) {
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [list_area, preview_area, input_area] = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(4),
+            Constraint::Length(if self.editing.is_some() { 3 } else { 0 }),
+        ])
+        .areas(inner);
+        let items = self
+            .fields()
+            .into_iter()
+            .map(|field| match field {
+                Field::Flag(i) => {
+                    let flag = &self.flags[i];
+                    let control = match (&flag.kind, &self.values[i]) {
+                        (FlagKind::Toggle, Some(_)) => "[x]".to_string(),
+                        (FlagKind::Toggle, None) => "[ ]".to_string(),
+                        (FlagKind::Choice(_), Some(value)) => format!("< {} >", value),
+                        (FlagKind::Choice(_), None) => "< off >".to_string(),
+                        (FlagKind::Text, Some(value)) => format!("\"{}\"", value),
+                        (FlagKind::Text, None) => "\"\"".to_string(),
+                    };
+                    let style = match self.values[i] {
+                        Some(_) => Style::new().fg(Color::Green),
+                        None => Style::new().fg(Color::DarkGray),
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("{:<16} ", flag.name), Style::new().bold()),
+                        Span::styled(format!("{:<28} ", control), style),
+                        Span::raw(flag.flag.clone()).dark_gray(),
+                    ]))
+                }
+                Field::Arg(i) => ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:<16} ", format!("arg {}", i + 1)),
+                        Style::new().bold(),
+                    ),
+                    Span::raw(self.args[i].clone()),
+                ])),
+            })
+            .collect::<Vec<_>>();
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        let list = List::new(items).highlight_style(
+            Style::new()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+        StatefulWidget::render(list, list_area, buf, &mut state);
+        let preview = format!("{} {}", self.exe_path, self.args().join(" "));
+        let title = if self.changed {
+            "command line (not saved)"
+        } else {
+            "command line"
+        };
+        Paragraph::new(preview)
+            .block(Block::new().borders(Borders::TOP).title(title))
+            .wrap(Wrap { trim: true })
+            .render(preview_area, buf);
+        if let Some((_, input)) = &self.editing {
+            input.render("value, empty to remove", input_area, buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn form(args: &[&str]) -> ArgsForm {
+        let cmd: CmdInfo = serde_json::from_value(serde_json::json!({
+            "name": "run",
+            "desc": "comfyui",
+            "command": "ComfyRun",
+            "exe_path": "python",
+            "args": args,
+            "flags": [
+                { "name": "listen", "flag": "--listen" },
+                { "name": "port", "flag": "--port", "kind": "text" },
+                { "name": "vram", "flag": "--vram", "kind": { "choice": ["low", "high"] } }
+            ]
+        }))
+        .unwrap();
+        ArgsForm::new(&cmd)
+    }
+
+    #[test]
+    fn flags_keep_their_place() {
+        let args = ["--port", "8188", "main.py", "--listen", "--extra"];
+        let mut form = form(&args);
+        assert_eq!(form.args(), args);
+        form.values[2] = Some("low".to_string());
+        form.remove_arg(0);
+        assert_eq!(
+            form.args(),
+            ["--port", "8188", "--listen", "--extra", "--vram", "low"]
+        );
+    }
+
+    #[test]
+    fn flags_without_a_value_are_off() {
+        let mut form = form(&["main.py", "--port", "--listen"]);
+        assert_eq!(form.values, [Some(String::new()), None, None]);
+        assert_eq!(form.args(), ["main.py", "--listen"]);
+        form.values[1] = Some("80".to_string());
+        assert_eq!(form.args(), ["main.py", "--port", "80", "--listen"]);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::command::DirtyPolicy;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+/// how long one git command may take, a fetch from a server that stopped answering
+/// would otherwise hang whatever waits for it
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+/// what an update changed, enough to go back to `previous`
+#[derive(Debug, Clone)]
+pub struct Update {
+    pub previous: String,
+    pub current: String,
+    /// local changes were stashed around the fast-forward and put back
+    pub stashed: bool,
+    /// why the stashed changes could not be put back, they stay in `git stash` then
+    pub stash_error: Option<String>,
+    pub commits: Vec<String>,
+    pub files: Vec<String>,
+}
+
+/// stdout of `git args` run in `dir`, stderr as the error when it fails, git never
+/// asks for credentials and is killed after `TIMEOUT`
+async fn git(dir: &str, args: &[&str]) -> Result<String, String> {
+    git_until(dir, args, None).await
+}
+
+/// like `git`, but also killed once `deadline` passes
+async fn git_until(dir: &str, args: &[&str], deadline: Option<Instant>) -> Result<String, String> {
+    let limit = deadline.map_or(TIMEOUT, |deadline| {
+        deadline
+            .saturating_duration_since(Instant::now())
+            .min(TIMEOUT)
+    });
+    let mut command = Command::new("git");
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GCM_INTERACTIVE", "never")
+        .kill_on_drop(true);
+    if !dir.is_empty() {
+        command.current_dir(dir);
+    }
+    let output = tokio::time::timeout(limit, command.output())
+        .await
+        .map_err(|_| {
+            if limit < TIMEOUT {
+                format!("git {} :ran past the deadline", args[0])
+            } else {
+                format!("git {} :timed out after {}s", args[0], TIMEOUT.as_secs())
+            }
+        })?
+        .map_err(|err| format!("git {} :{}", args[0], err))?;
+    if !output.status.success() {
+        // a conflicting merge or stash pop only explains itself on stdout
+        let reason = match String::from_utf8_lossy(&output.stderr).trim() {
+            "" => String::from_utf8_lossy(&output.stdout).trim().to_string(),
+            stderr => stderr.to_string(),
+        };
+        return Err(format!("git {} :{}", args.join(" "), reason));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+/// `git log --oneline` output, one `<hash> <subject>` per commit
+fn commits(log: &str) -> Vec<String> {
+    log.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `git diff --stat` output without the `n files changed` summary at the end
+fn files(stat: &str) -> Vec<String> {
+    let mut files: Vec<String> = stat
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    if files.last().is_some_and(|last| !last.contains('|')) {
+        files.pop();
+    }
+    files
+}
+
+/// `git rev-list --left-right --count` output
+fn ahead_behind(counts: &str) -> Option<(usize, usize)> {
+    let (ahead, behind) = counts.trim().split_once(char::is_whitespace)?;
+    Some((ahead.trim().parse().ok()?, behind.trim().parse().ok()?))
+}
+
+/// fetches and fast-forwards to the upstream branch, remembering where it started.
+/// with `DirtyPolicy::Stash` local changes are stashed only when there is something
+/// to merge and are popped again whether or not the merge worked. every git command
+/// but the pop is killed once `deadline` passes, so local changes are never left behind
+pub async fn update(
+    dir: &str,
+    dirty: DirtyPolicy,
+    deadline: Option<Instant>,
+) -> Result<Update, String> {
+    let previous = git_until(dir, &["rev-parse", "HEAD"], deadline).await?;
+    let changes = git_until(dir, &["status", "--porcelain"], deadline).await?;
+    if !changes.is_empty() && dirty == DirtyPolicy::Refuse {
+        return Err(format!(
+            "{} uncommitted changes, commit or stash them first",
+            changes.lines().count()
+        ));
+    }
+    git_until(dir, &["fetch"], deadline).await?;
+    let log = git_until(dir, &["log", "--oneline", "HEAD..@{u}"], deadline).await?;
+    let stat = git_until(dir, &["diff", "--stat", "HEAD", "@{u}"], deadline).await?;
+    let (commits, files) = (commits(&log), files(&stat));
+    if commits.is_empty() {
+        return Ok(Update {
+            current: previous.clone(),
+            previous,
+            stashed: false,
+            stash_error: None,
+            commits,
+            files,
+        });
+    }
+    let stashed = !changes.is_empty();
+    if stashed {
+        let message = format!("tool update from {}", short(&previous));
+        git_until(
+            dir,
+            &["stash", "push", "--include-untracked", "-m", &message],
+            deadline,
+        )
+        .await?;
+    }
+    let merged = git_until(dir, &["merge", "--ff-only", "@{u}"], deadline).await;
+    let stash_error = if stashed {
+        git(dir, &["stash", "pop"]).await.err()
+    } else {
+        None
+    };
+    if let Err(err) = merged {
+        return Err(match stash_error {
+            Some(pop) => format!("{}, local changes are still in git stash :{}", err, pop),
+            None => err,
+        });
+    }
+    let current = git(dir, &["rev-parse", "HEAD"]).await?;
+    Ok(Update {
+        previous,
+        current,
+        stashed,
+        stash_error,
+        commits,
+        files,
+    })
+}
+
+/// moves the checked out branch back to `commit`, keeping local changes to files
+/// the two commits do not disagree on and refusing when they do
+pub async fn reset(dir: &str, commit: &str) -> Result<(), String> {
+    git(dir, &["reset", "--keep", commit]).await.map(|_| ())
+}
+
+pub fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(8)]
+}
+
+/// where a checkout stands, `ahead_behind` is against the last fetched upstream
+#[derive(Debug, Clone, Default)]
+pub struct RepoInfo {
+    pub branch: String,
+    pub date: String,
+    pub ahead_behind: Option<(usize, usize)>,
+}
+
+pub async fn info(dir: &str) -> Result<RepoInfo, String> {
+    let branch = git(dir, &["rev-parse", "--abbrev-ref", "HEAD"]).await?;
+    let date = git(dir, &["log", "-1", "--format=%cs"]).await?;
+    // fails without an upstream, there is nothing to compare then
+    let ahead_behind = git(dir, &["rev-list", "--left-right", "--count", "HEAD...@{u}"])
+        .await
+        .ok()
+        .and_then(|counts| ahead_behind(&counts));
+    Ok(RepoInfo {
+        branch,
+        date,
+        ahead_behind,
+    })
+}
+
+pub async fn fetch(dir: &str) -> Result<(), String> {
+    git(dir, &["fetch"]).await.map(|_| ())
+}
+
+/// whether `path` differs between the two commits
+pub async fn changed(dir: &str, from: &str, to: &str, path: &str) -> Result<bool, String> {
+    let names = git(dir, &["diff", "--name-only", from, to, "--", path]).await?;
+    Ok(!names.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commits_are_one_per_line() {
+        let log = "4876fee break it\nb04f750 good\n";
+        assert_eq!(commits(log), ["4876fee break it", "b04f750 good"]);
+        assert!(commits("").is_empty());
+    }
+
+    #[test]
+    fn files_leave_out_the_summary() {
+        let stat = " main.py          | 2 +-\n requirements.txt | 1 +\n 2 files changed, 2 insertions(+), 1 deletion(-)";
+        assert_eq!(
+            files(stat),
+            ["main.py          | 2 +-", "requirements.txt | 1 +"]
+        );
+        assert!(files("").is_empty());
+    }
+
+    #[test]
+    fn ahead_behind_reads_both_counts() {
+        assert_eq!(ahead_behind("1\t3"), Some((1, 3)));
+        assert_eq!(ahead_behind("0\t0\n"), Some((0, 0)));
+        assert_eq!(ahead_behind("fatal"), None);
+    }
+}
@@ -0,0 +1,369 @@
+use crate::command::{CmdInfo, MenuCommand, Retry};
+use crate::explorer::{format_duration, format_time};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::widgets::{
+    Block, Borders, Paragraph, Row, StatefulWidget, Table, TableState, Widget, Wrap,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// next to tool.json, one finished run per line
+const FILE: &str = "history.jsonl";
+/// runs kept in the file, older ones are dropped when it grows past twice as many
+const KEEP: usize = 1000;
+/// output lines kept with every run
+pub const TAIL: usize = 20;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// what started a run
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Trigger {
+    /// Enter in the menu, start all
+    Manual,
+    /// autostart when the tool launches
+    Scheduled,
+    /// restarts, threshold actions, rollbacks and restores
+    Restart,
+    /// a step of a pipeline
+    Pipeline,
+    /// another attempt after a failed run
+    Retry,
+}
+
+/// how a run ended
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Exit(i32),
+    /// ended by a signal, no exit code
+    Killed,
+    /// could not be started or waited for, or a pipeline step failed
+    Failed(String),
+    Cancelled,
+    /// every step of a pipeline went through
+    Finished,
+    /// stopped after running for the configured seconds
+    TimedOut(u64),
+    /// a service that exited after it was asked to stop
+    Stopped,
+}
+impl Outcome {
+    pub fn succeeded(&self) -> bool {
+        matches!(
+            self,
+            Outcome::Exit(0) | Outcome::Finished | Outcome::Stopped
+        )
+    }
+    pub fn label(&self) -> String {
+        match self {
+            Outcome::Exit(code) => format!("exit {}", code),
+            Outcome::Killed => "killed".to_string(),
+            Outcome::Failed(err) => format!("failed :{}", err),
+            Outcome::Cancelled => "cancelled".to_string(),
+            Outcome::Finished => "finished".to_string(),
+            Outcome::TimedOut(secs) => format!("timed out after {}s", secs),
+            Outcome::Stopped => "stopped".to_string(),
+        }
+    }
+    /// a failure `retry` asks to try again, runs that were killed or never started are not
+    pub fn retryable(&self, retry: &Retry) -> bool {
+        match self {
+            Outcome::TimedOut(_) => true,
+            Outcome::Exit(code) => *code != 0 && (retry.on.is_empty() || retry.on.contains(code)),
+            _ => false,
+        }
+    }
+}
+
+/// one launch of a menu entry
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Run {
+    /// tells the runs still going apart, not stored
+    #[serde(skip)]
+    pub id: u64,
+    pub command: MenuCommand,
+    pub name: String,
+    pub trigger: Trigger,
+    /// unix millis
+    pub started: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<Outcome>,
+    /// last output lines
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tail: Vec<String>,
+}
+
+impl Run {
+    pub fn start(cmd: &CmdInfo, trigger: Trigger) -> Self {
+        Run {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            command: cmd.command,
+            name: cmd.name.trim().to_string(),
+            trigger,
+            started: now(),
+            ended: None,
+            outcome: None,
+            tail: Vec::new(),
+        }
+    }
+    pub fn finish(mut self, outcome: Outcome, tail: Vec<String>) -> Self {
+        self.ended = Some(now());
+        self.outcome = Some(outcome);
+        self.tail = tail;
+        self
+    }
+    /// millis so far for a run still going
+    pub fn duration(&self) -> u64 {
+        self.ended.unwrap_or_else(now).saturating_sub(self.started)
+    }
+    pub fn result(&self) -> String {
+        self.outcome
+            .as_ref()
+            .map_or("running".to_string(), |o| o.label())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// adds a finished run to the file
+pub fn append(run: &Run) -> io::Result<()> {
+    let line = serde_json::to_string(run)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(FILE)?;
+    writeln!(file, "{}", line)?;
+    drop(file);
+    let text = fs::read_to_string(FILE)?;
+    let lines = text.lines().collect::<Vec<_>>();
+    if lines.len() > KEEP * 2 {
+        let kept = lines[lines.len() - KEEP..].join("\n");
+        fs::write(FILE, format!("{}\n", kept))?;
+    }
+    Ok(())
+}
+
+/// every stored run, oldest first, unreadable lines are skipped
+pub fn load() -> Vec<Run> {
+    let Ok(text) = fs::read_to_string(FILE) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// totals of one menu entry
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub name: String,
+    pub runs: usize,
+    pub succeeded: usize,
+    /// millis
+    pub average: u64,
+    pub last: u64,
+}
+impl Stats {
+    pub fn rate(&self) -> String {
+        format!("{}%", self.succeeded * 100 / self.runs.max(1))
+    }
+}
+
+/// finished runs grouped by entry name, most recently run first
+pub fn stats(runs: &[Run]) -> Vec<Stats> {
+    let mut grouped: BTreeMap<&str, Vec<&Run>> = BTreeMap::new();
+    for run in runs.iter().filter(|r| r.outcome.is_some()) {
+        grouped.entry(&run.name).or_default().push(run);
+    }
+    let mut stats = grouped
+        .into_iter()
+        .map(|(name, runs)| Stats {
+            name: name.to_string(),
+            runs: runs.len(),
+            succeeded: runs
+                .iter()
+                .filter(|r| r.outcome.as_ref().is_some_and(|o| o.succeeded()))
+                .count(),
+            average: runs.iter().map(|r| r.duration()).sum::<u64>() / runs.len() as u64,
+            last: runs.iter().map(|r| r.started).max().unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    stats.sort_by_key(|s| std::cmp::Reverse(s.last));
+    stats
+}
+
+/// the `tool history [name]` listing, the last runs and the totals per entry
+pub fn report(name: Option<&str>) -> String {
+    let runs = load()
+        .into_iter()
+        .filter(|r| name.is_none_or(|n| r.name == n))
+        .collect::<Vec<_>>();
+    if runs.is_empty() {
+        return "no runs recorded\n".to_string();
+    }
+    let mut out = format!(
+        "{:<17}  {:<20}  {:<9}  {:>9}  RESULT\n",
+        "STARTED (UTC)", "NAME", "TRIGGER", "DURATION"
+    );
+    for run in runs.iter().rev().take(30) {
+        out.push_str(&format!(
+            "{:<17}  {:<20}  {:<9}  {:>9}  {}\n",
+            format_time(run.started / 1000),
+            run.name,
+            format!("{:?}", run.trigger).to_lowercase(),
+            format_duration(run.duration() / 1000),
+            run.result()
+        ));
+    }
+    out.push_str(&format!(
+        "\n{:<20}  {:>5}  {:>5}  {:>9}  LAST (UTC)\n",
+        "NAME", "RUNS", "OK", "AVERAGE"
+    ));
+    for stat in stats(&runs) {
+        out.push_str(&format!(
+            "{:<20}  {:>5}  {:>5}  {:>9}  {}\n",
+            stat.name,
+            stat.runs,
+            stat.rate(),
+            format_duration(stat.average / 1000),
+            format_time(stat.last / 1000)
+        ));
+    }
+    out
+}
+
+/// totals per entry over the runs, newest first, with the output of the selected one
+#[derive(Debug, Default)]
+pub struct HistoryPanel {
+    pub runs: Vec<Run>,
+    state: TableState,
+    /// index into the newest first list of running and stored runs
+    selected: usize,
+}
+
+impl HistoryPanel {
+    pub fn reload(&mut self) {
+        self.runs = load();
+        self.selected = 0;
+    }
+    pub fn select(&mut self, step: isize, count: usize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(step)
+            .min(count.saturating_sub(1));
+    }
+    /// `active` are the runs still going, they are listed first
+    pub fn render(&mut self, block: Block, active: &[Run], area: Rect, buf: &mut Buffer) {
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let stats = stats(&self.runs);
+        let [stats_area, runs_area, tail_area] = Layout::vertical([
+            Constraint::Length(stats.len().min(8) as u16 + 1),
+            Constraint::Percentage(50),
+            Constraint::Min(3),
+        ])
+        .areas(inner);
+        let header = Style::new().add_modifier(Modifier::BOLD);
+        let rows = stats
+            .iter()
+            .map(|s| {
+                Row::new(vec![
+                    s.name.clone(),
+                    s.runs.to_string(),
+                    s.rate(),
+                    format_duration(s.average / 1000),
+                    format_time(s.last / 1000),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let widths = [
+            Constraint::Length(20),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(9),
+            Constraint::Fill(1),
+        ];
+        Widget::render(
+            Table::new(rows, widths).header(
+                Row::new(vec!["NAME", "RUNS", "OK", "AVERAGE", "LAST (UTC)"]).style(header),
+            ),
+            stats_area,
+            buf,
+        );
+        let runs = active
+            .iter()
+            .rev()
+            .chain(self.runs.iter().rev())
+            .collect::<Vec<_>>();
+        self.selected = self.selected.min(runs.len().saturating_sub(1));
+        let rows = runs
+            .iter()
+            .map(|r| {
+                let row = Row::new(vec![
+                    format_time(r.started / 1000),
+                    r.name.clone(),
+                    format!("{:?}", r.trigger).to_lowercase(),
+                    format_duration(r.duration() / 1000),
+                    r.result(),
+                ]);
+                match &r.outcome {
+                    None => row.fg(Color::Yellow),
+                    Some(o) if !o.succeeded() => row.fg(Color::Red),
+                    Some(_) => row,
+                }
+            })
+            .collect::<Vec<_>>();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(17),
+                Constraint::Length(20),
+                Constraint::Length(10),
+                Constraint::Length(9),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                "STARTED (UTC)",
+                "NAME",
+                "TRIGGER",
+                "DURATION",
+                "RESULT",
+            ])
+            .style(header),
+        )
+        .block(Block::new().borders(Borders::TOP))
+        .row_highlight_style(
+            Style::new()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+        self.state
+            .select(Some(self.selected).filter(|_| !runs.is_empty()));
+        StatefulWidget::render(table, runs_area, buf, &mut self.state);
+        let Some(run) = runs.get(self.selected) else {
+            return;
+        };
+        Paragraph::new(run.tail.join("\n"))
+            .block(
+                Block::new()
+                    .borders(Borders::TOP)
+                    .title(format!("last output of {}", run.name)),
+            )
+            .wrap(Wrap { trim: true })
+            .render(tail_area, buf);
+    }
+}
@@ -0,0 +1,103 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Stylize};
+use ratatui::widgets::{Block, Borders, Paragraph, Widget};
+
+const HISTORY_LIMIT: usize = 50;
+
+/// what a key press did to the line being edited
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    Submit(String),
+    Eof,
+    Cancel,
+    Edited,
+}
+
+/// single line editor with a submit history
+#[derive(Debug, Clone, Default)]
+pub struct LineInput {
+    pub buffer: String,
+    history: Vec<String>,
+    position: Option<usize>,
+}
+
+impl LineInput {
+    /// starts with `history` to go through with ▲ ▼, oldest first
+    pub fn with_history(history: Vec<String>) -> Self {
+        LineInput {
+            history,
+            ..LineInput::default()
+        }
+    }
+    pub fn handle_key(&mut self, key: &KeyEvent) -> InputEvent {
+        match key.code {
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => InputEvent::Eof,
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.buffer.clear();
+                InputEvent::Edited
+            }
+            KeyCode::Char(c) => {
+                self.buffer.push(c);
+                InputEvent::Edited
+            }
+            KeyCode::Backspace => {
+                self.buffer.pop();
+                InputEvent::Edited
+            }
+            KeyCode::Esc => InputEvent::Cancel,
+            KeyCode::Enter => {
+                let line = std::mem::take(&mut self.buffer);
+                if !line.is_empty() && self.history.last() != Some(&line) {
+                    self.history.push(line.clone());
+                    if self.history.len() > HISTORY_LIMIT {
+                        self.history.remove(0);
+                    }
+                }
+                self.position = None;
+                InputEvent::Submit(line)
+            }
+            KeyCode::Up => {
+                let position = match self.position {
+                    Some(p) => p.saturating_sub(1),
+                    None => self.history.len().saturating_sub(1),
+                };
+                if let Some(line) = self.history.get(position) {
+                    self.buffer = line.clone();
+                    self.position = Some(position);
+                }
+                InputEvent::Edited
+            }
+            KeyCode::Down => {
+                match self.position {
+                    Some(p) if p + 1 < self.history.len() => {
+                        self.buffer = self.history[p + 1].clone();
+                        self.position = Some(p + 1);
+                    }
+                    _ => {
+                        self.buffer.clear();
+                        self.position = None;
+                    }
+                }
+                InputEvent::Edited
+            }
+            _ => InputEvent::Edited,
+        }
+    }
+    pub fn render(&self, title: &str, area: Rect, buf: &mut Buffer) {
+        // keep the cursor end visible on long lines
+        let room = (area.width as usize).saturating_sub(5);
+        let skip = self.buffer.chars().count().saturating_sub(room);
+        let visible = self.buffer.chars().skip(skip).collect::<String>();
+        Paragraph::new(format!("> {}█", visible))
+            .block(
+                Block::new()
+                    .title(title.to_string())
+                    .borders(Borders::ALL)
+                    .border_style(Color::Red),
+            )
+            .bold()
+            .render(area, buf);
+    }
+}
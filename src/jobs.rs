@@ -0,0 +1,290 @@
+use crate::explorer::format_duration;
+use crate::history::Outcome;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::widgets::{Block, Row, StatefulWidget, Table, TableState};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{watch, Semaphore};
+
+/// ended jobs still listed, older ones are dropped
+const KEEP_ENDED: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobState {
+    /// waiting for one of `max_jobs` slots
+    Queued,
+    Running,
+    /// asked to stop, the process has not exited yet
+    Cancelling,
+    Ended(Outcome),
+}
+
+/// a one-shot command or quick action started from the tool
+#[derive(Debug)]
+pub struct Job {
+    pub id: u64,
+    /// the menu entry or quick action, only one job per key at a time
+    key: String,
+    pub name: String,
+    pub state: JobState,
+    /// process of the attempt running now
+    pub pid: Option<u32>,
+    queued: Instant,
+    started: Option<Instant>,
+    ended: Option<Instant>,
+    cancel: watch::Sender<bool>,
+}
+
+impl Job {
+    pub fn active(&self) -> bool {
+        !matches!(self.state, JobState::Ended(_))
+    }
+    /// seconds waited while queued, else seconds run
+    pub fn elapsed(&self) -> u64 {
+        let end = self.ended.unwrap_or_else(Instant::now);
+        end.duration_since(self.started.unwrap_or(self.queued))
+            .as_secs()
+    }
+    fn label(&self) -> String {
+        match &self.state {
+            JobState::Queued => "queued".to_string(),
+            JobState::Running => "running".to_string(),
+            JobState::Cancelling => "cancelling".to_string(),
+            JobState::Ended(outcome) => outcome.label(),
+        }
+    }
+}
+
+/// handed to the task running a job
+#[derive(Debug, Clone)]
+pub struct Ticket {
+    pub id: u64,
+    cancelled: watch::Receiver<bool>,
+}
+
+impl Ticket {
+    /// resolves once the job is cancelled from the jobs panel
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.cancelled.clone();
+        if cancelled.wait_for(|c| *c).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// every job started since launch, at most `max` run at the same time
+#[derive(Debug)]
+pub struct Jobs {
+    pub list: Vec<Job>,
+    pub max: usize,
+    slots: Arc<Semaphore>,
+    next_id: u64,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        Jobs::new(1)
+    }
+}
+
+impl Jobs {
+    pub fn new(max: usize) -> Self {
+        let max = max.max(1);
+        Jobs {
+            list: Vec::new(),
+            max,
+            slots: Arc::new(Semaphore::new(max)),
+            next_id: 1,
+        }
+    }
+    /// queues a job, `None` while one with the same key is queued or running
+    pub fn add(&mut self, key: &str, name: &str) -> Option<(Ticket, Arc<Semaphore>)> {
+        if self.list.iter().any(|j| j.active() && j.key == key) {
+            return None;
+        }
+        let (cancel, cancelled) = watch::channel(false);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.list.push(Job {
+            id,
+            key: key.to_string(),
+            name: name.to_string(),
+            state: JobState::Queued,
+            pid: None,
+            queued: Instant::now(),
+            started: None,
+            ended: None,
+            cancel,
+        });
+        Some((Ticket { id, cancelled }, Arc::clone(&self.slots)))
+    }
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Job> {
+        self.list.iter_mut().find(|j| j.id == id)
+    }
+    /// the job got a slot
+    pub fn start(&mut self, id: u64) {
+        if let Some(job) = self.get_mut(id).filter(|j| j.state == JobState::Queued) {
+            job.state = JobState::Running;
+            job.started = Some(Instant::now());
+        }
+    }
+    pub fn finish(&mut self, id: u64, outcome: Outcome) {
+        if let Some(job) = self.get_mut(id) {
+            job.state = JobState::Ended(outcome);
+            job.ended = Some(Instant::now());
+            job.pid = None;
+        }
+        let ended = self.list.iter().filter(|j| !j.active()).count();
+        let mut drop = ended.saturating_sub(KEEP_ENDED);
+        self.list.retain(|j| {
+            let old = drop > 0 && !j.active();
+            if old {
+                drop -= 1;
+            }
+            !old
+        });
+    }
+    /// asks the job to stop, false when it already ended
+    pub fn cancel(&mut self, id: u64) -> bool {
+        let Some(job) = self.get_mut(id).filter(|j| j.active()) else {
+            return false;
+        };
+        job.state = JobState::Cancelling;
+        let _ = job.cancel.send(true);
+        true
+    }
+    pub fn running(&self) -> usize {
+        self.list
+            .iter()
+            .filter(|j| matches!(j.state, JobState::Running | JobState::Cancelling))
+            .count()
+    }
+    pub fn queued(&self) -> usize {
+        self.list
+            .iter()
+            .filter(|j| j.state == JobState::Queued)
+            .count()
+    }
+}
+
+/// the jobs newest first
+#[derive(Debug, Default)]
+pub struct JobsPanel {
+    state: TableState,
+    selected: usize,
+}
+
+impl JobsPanel {
+    pub fn select(&mut self, step: isize, count: usize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(step)
+            .min(count.saturating_sub(1));
+    }
+    /// id of the highlighted job
+    pub fn selected(&self, jobs: &Jobs) -> Option<u64> {
+        jobs.list.iter().rev().nth(self.selected).map(|j| j.id)
+    }
+    pub fn render(&mut self, block: Block, jobs: &Jobs, area: Rect, buf: &mut Buffer) {
+        self.selected = self.selected.min(jobs.list.len().saturating_sub(1));
+        let rows = jobs
+            .list
+            .iter()
+            .rev()
+            .map(|job| {
+                let row = Row::new(vec![
+                    job.id.to_string(),
+                    job.name.clone(),
+                    job.label(),
+                    format_duration(job.elapsed()),
+                    job.pid.map_or(String::new(), |pid| pid.to_string()),
+                ]);
+                match &job.state {
+                    JobState::Queued => row.fg(Color::DarkGray),
+                    JobState::Running => row.fg(Color::Yellow),
+                    JobState::Cancelling => row.fg(Color::Magenta),
+                    JobState::Ended(outcome) if !outcome.succeeded() => row.fg(Color::Red),
+                    JobState::Ended(_) => row,
+                }
+            })
+            .collect::<Vec<_>>();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(5),
+                Constraint::Length(24),
+                Constraint::Fill(1),
+                Constraint::Length(9),
+                Constraint::Length(8),
+            ],
+        )
+        .header(
+            Row::new(vec!["ID", "NAME", "STATE", "ELAPSED", "PID"])
+                .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .block(block)
+        .row_highlight_style(
+            Style::new()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+        self.state
+            .select(Some(self.selected).filter(|_| !jobs.list.is_empty()));
+        StatefulWidget::render(table, area, buf, &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn one_job_per_key_at_a_time() {
+        let mut jobs = Jobs::new(2);
+        let (first, _) = jobs.add("Custom/backup", "backup").unwrap();
+        assert!(jobs.add("Custom/backup", "backup").is_none());
+        assert!(jobs.add("Custom/prune", "backup").is_some());
+        jobs.start(first.id);
+        assert!(jobs.add("Custom/backup", "backup").is_none());
+        jobs.finish(first.id, Outcome::Exit(0));
+        assert!(jobs.add("Custom/backup", "backup").is_some());
+        assert_eq!(jobs.queued(), 2);
+    }
+
+    #[test]
+    fn cancel_and_finish_keep_the_latest_ended_jobs() {
+        let mut jobs = Jobs::new(1);
+        let (running, _) = jobs.add("running", "running").unwrap();
+        jobs.start(running.id);
+        for i in 0..KEEP_ENDED + 5 {
+            let (ticket, _) = jobs.add(&i.to_string(), "job").unwrap();
+            assert!(jobs.cancel(ticket.id));
+            assert_eq!(jobs.get_mut(ticket.id).unwrap().state, JobState::Cancelling);
+            jobs.finish(ticket.id, Outcome::Cancelled);
+            assert!(!jobs.cancel(ticket.id));
+        }
+        assert_eq!(jobs.list.len(), KEEP_ENDED + 1);
+        assert_eq!(jobs.list[0].id, running.id);
+        assert_eq!(jobs.list[1].key, "5");
+        assert_eq!(jobs.running(), 1);
+    }
+
+    #[tokio::test]
+    async fn ticket_resolves_once_cancelled() {
+        let mut jobs = Jobs::new(1);
+        let (ticket, _) = jobs.add("job", "job").unwrap();
+        let waiting = tokio::time::timeout(Duration::from_millis(50), ticket.cancelled());
+        assert!(waiting.await.is_err());
+        jobs.cancel(ticket.id);
+        let cancelled = tokio::time::timeout(Duration::from_secs(1), ticket.cancelled());
+        assert!(cancelled.await.is_ok());
+        // the ticket of a job that is gone never resolves
+        let (gone, _) = jobs.add("gone", "gone").unwrap();
+        jobs.list.retain(|j| j.id != gone.id);
+        let never = tokio::time::timeout(Duration::from_millis(50), gone.cancelled());
+        assert!(never.await.is_err());
+    }
+}
@@ -0,0 +1,402 @@
+use crate::command::Limits;
+use crate::explorer::format_bytes;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::PathBuf;
+
+const MB: u64 = 1024 * 1024;
+#[cfg(target_os = "linux")]
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+/// set for a pty child, whose builder has no pre-exec hook, the tool then starts as a launcher
+/// that limits itself and execs the real program
+const LAUNCH_ENV: &str = "TOOL_LIMITS";
+
+/// what the child puts on itself between fork and exec, so whatever it starts right away
+/// is limited too
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Setup {
+    nice: Option<i32>,
+    cpus: Vec<usize>,
+    address_space: Option<u64>,
+    open_files: Option<u64>,
+    /// `cgroup.procs` of the group made for the command
+    procs: Option<PathBuf>,
+}
+
+impl Setup {
+    /// makes the memory group of `name` when asked for, failures come back as `[error]` lines
+    pub fn new(name: &str, limits: &Limits) -> (Setup, Vec<String>) {
+        let mut failed = Vec::new();
+        let procs = limits
+            .memory_mb
+            .and_then(|mb| match make_group(name, mb * MB) {
+                Ok(procs) => Some(procs),
+                Err(err) => {
+                    failed.push(format!("[error] memory :{}", err));
+                    None
+                }
+            });
+        let setup = Setup {
+            nice: limits.nice,
+            cpus: limits.cpus.clone(),
+            address_space: limits.address_space_mb.map(|mb| mb * MB),
+            open_files: limits.open_files,
+            procs,
+        };
+        (setup, failed)
+    }
+    /// the child goes into a memory group, which `release` removes after it exited
+    pub fn is_grouped(&self) -> bool {
+        self.procs.is_some()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.nice.is_none()
+            && self.cpus.is_empty()
+            && self.address_space.is_none()
+            && self.open_files.is_none()
+            && self.procs.is_none()
+    }
+    /// applies the setup in the child before exec, what did not take shows in `report`
+    #[cfg(unix)]
+    pub fn install(&self, command: &mut tokio::process::Command) {
+        if self.is_empty() {
+            return;
+        }
+        let setup = self.clone();
+        let procs = setup.procs_path();
+        // SAFETY: `enter` only makes syscalls on data prepared before the fork
+        unsafe {
+            command.pre_exec(move || {
+                setup.enter(procs.as_deref(), |_, _| {});
+                Ok(())
+            });
+        }
+    }
+    /// argv that runs `argv` through the tool as a launcher, with the variable to set
+    #[cfg(unix)]
+    pub fn launcher(&self, argv: Vec<String>) -> io::Result<(Vec<String>, Option<[String; 2]>)> {
+        if self.is_empty() {
+            return Ok((argv, None));
+        }
+        let exe = std::env::current_exe()?.to_string_lossy().into_owned();
+        let setup = serde_json::to_string(self)?;
+        let argv = std::iter::once(exe).chain(argv).collect();
+        Ok((argv, Some([LAUNCH_ENV.to_string(), setup])))
+    }
+    #[cfg(unix)]
+    fn procs_path(&self) -> Option<std::ffi::CString> {
+        use std::os::unix::ffi::OsStrExt;
+        let path = self.procs.as_ref()?;
+        std::ffi::CString::new(path.as_os_str().as_bytes()).ok()
+    }
+    /// limits the calling process, allocation free so it can run between fork and exec
+    #[cfg(unix)]
+    fn enter(&self, procs: Option<&std::ffi::CStr>, mut failed: impl FnMut(&str, io::Error)) {
+        if let Some(nice) = self.nice {
+            if let Err(err) = check(unsafe { libc::setpriority(libc::PRIO_PROCESS as _, 0, nice) })
+            {
+                failed("nice", err);
+            }
+        }
+        #[cfg(target_os = "linux")]
+        {
+            if !self.cpus.is_empty() {
+                let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+                for &cpu in &self.cpus {
+                    unsafe { libc::CPU_SET(cpu, &mut set) };
+                }
+                let size = std::mem::size_of::<libc::cpu_set_t>();
+                if let Err(err) = check(unsafe { libc::sched_setaffinity(0, size, &set) }) {
+                    failed("cpus", err);
+                }
+            }
+            let rlimits = [
+                ("address space", libc::RLIMIT_AS, self.address_space),
+                ("open files", libc::RLIMIT_NOFILE, self.open_files),
+            ];
+            for (what, resource, value) in rlimits {
+                let Some(value) = value else {
+                    continue;
+                };
+                let limit = libc::rlimit {
+                    rlim_cur: value,
+                    rlim_max: value,
+                };
+                if let Err(err) = check(unsafe { libc::setrlimit(resource, &limit) }) {
+                    failed(what, err);
+                }
+            }
+            if let Some(procs) = procs {
+                // 0 moves the writing process
+                let fd = unsafe { libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC) };
+                let moved = check(fd).and_then(|_| {
+                    let written = unsafe { libc::write(fd, b"0".as_ptr().cast(), 1) };
+                    unsafe { libc::close(fd) };
+                    check(written as i32)
+                });
+                if let Err(err) = moved {
+                    failed("memory", err);
+                }
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = procs;
+    }
+}
+
+/// when the tool was started as the launcher of a pty command, limits itself and becomes
+/// the command, returns otherwise
+#[cfg(unix)]
+pub fn launch() {
+    use std::os::unix::process::CommandExt;
+    let Some(setup) = std::env::var_os(LAUNCH_ENV) else {
+        return;
+    };
+    std::env::remove_var(LAUNCH_ENV);
+    let mut argv = std::env::args_os().skip(1);
+    let Some(program) = argv.next() else {
+        std::process::exit(127);
+    };
+    match serde_json::from_str::<Setup>(&setup.to_string_lossy()) {
+        Ok(setup) => setup.enter(setup.procs_path().as_deref(), |what, err| {
+            eprintln!("[error] {} :{}", what, err)
+        }),
+        Err(err) => eprintln!("[error] limits :{}", err),
+    }
+    let err = std::process::Command::new(&program).args(argv).exec();
+    eprintln!("Failed to execute {} :{}", program.to_string_lossy(), err);
+    std::process::exit(127);
+}
+#[cfg(not(unix))]
+pub fn launch() {}
+
+/// true once a launched pty child exec'd the real program, its limits are set then
+#[cfg(target_os = "linux")]
+pub fn settled(pid: u32) -> bool {
+    let exe = std::fs::read_link(format!("/proc/{}/exe", pid));
+    match (exe, std::env::current_exe()) {
+        (Ok(exe), Ok(own)) => exe != own,
+        _ => true,
+    }
+}
+#[cfg(not(target_os = "linux"))]
+pub fn settled(_pid: u32) -> bool {
+    true
+}
+
+/// one line per limit telling what took effect on `pid`, failures start with `[error]`,
+/// `pty` children on windows get no priority class
+pub fn report(pid: u32, name: &str, limits: &Limits, setup: &Setup, pty: bool) -> Vec<String> {
+    let mut applied = Vec::new();
+    let mut report = |what: &str, result: io::Result<String>| match result {
+        Ok(value) => applied.push(format!("{} {}", what, value)),
+        Err(err) => applied.push(format!("[error] {} :{}", what, err)),
+    };
+    if let Some(nice) = limits.nice {
+        report("nice", get_nice(pid, nice, pty));
+    }
+    if !limits.cpus.is_empty() {
+        report("cpus", get_affinity(pid, &limits.cpus));
+    }
+    if let Some(mb) = limits.address_space_mb {
+        report(
+            "address space",
+            get_rlimit(pid, Resource::AddressSpace, mb * MB),
+        );
+    }
+    if let Some(files) = limits.open_files {
+        report("open files", get_rlimit(pid, Resource::OpenFiles, files));
+    }
+    // `Setup::new` already told why there is no group
+    if setup.procs.is_some() {
+        report("memory", get_memory(pid, name));
+    }
+    applied
+}
+
+enum Resource {
+    AddressSpace,
+    OpenFiles,
+}
+
+#[cfg(not(target_os = "linux"))]
+fn unsupported() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "not supported on this platform")
+}
+
+/// the child kept what it inherited, `wanted` did not take
+fn not_applied(still: impl std::fmt::Display) -> io::Error {
+    io::Error::other(format!("not applied, still {}", still))
+}
+
+/// errno of a failed libc call
+#[cfg(unix)]
+fn check(ret: i32) -> io::Result<()> {
+    if ret == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn get_nice(pid: u32, nice: i32, _pty: bool) -> io::Result<String> {
+    let effective = unsafe { libc::getpriority(libc::PRIO_PROCESS as _, pid as _) };
+    if effective != nice {
+        return Err(not_applied(effective));
+    }
+    Ok(nice.to_string())
+}
+/// windows has no niceness, a piped child was created with the matching priority class
+#[cfg(windows)]
+fn get_nice(_pid: u32, nice: i32, pty: bool) -> io::Result<String> {
+    if pty {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "no priority class for pty commands",
+        ));
+    }
+    let class = match nice {
+        ..=-15 => "high",
+        -14..=-5 => "above normal",
+        -4..=4 => "normal",
+        5..=14 => "below normal",
+        15.. => "idle",
+    };
+    Ok(format!("{} (priority class {})", nice, class))
+}
+
+/// `CREATE_*` priority class flag for `nice`
+#[cfg(windows)]
+pub fn priority_class(nice: i32) -> u32 {
+    match nice {
+        ..=-15 => 0x0000_0080,
+        -14..=-5 => 0x0000_8000,
+        -4..=4 => 0x0000_0020,
+        5..=14 => 0x0000_4000,
+        15.. => 0x0000_0040,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_affinity(pid: u32, cpus: &[usize]) -> io::Result<String> {
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+    let mut effective: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    check(unsafe { libc::sched_getaffinity(pid as _, size, &mut effective) })?;
+    let effective = (0..libc::CPU_SETSIZE as usize)
+        .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &effective) })
+        .collect::<Vec<_>>();
+    let listed = effective
+        .iter()
+        .map(|cpu| cpu.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    if effective.iter().any(|cpu| !cpus.contains(cpu)) {
+        return Err(not_applied(listed));
+    }
+    Ok(listed)
+}
+#[cfg(not(target_os = "linux"))]
+fn get_affinity(_pid: u32, _cpus: &[usize]) -> io::Result<String> {
+    Err(unsupported())
+}
+
+#[cfg(target_os = "linux")]
+fn get_rlimit(pid: u32, resource: Resource, value: u64) -> io::Result<String> {
+    let resource = match resource {
+        Resource::AddressSpace => libc::RLIMIT_AS,
+        Resource::OpenFiles => libc::RLIMIT_NOFILE,
+    };
+    let mut effective = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    check(unsafe { libc::prlimit(pid as _, resource, std::ptr::null(), &mut effective) })?;
+    let shown = if resource == libc::RLIMIT_AS {
+        format_bytes(effective.rlim_cur)
+    } else {
+        effective.rlim_cur.to_string()
+    };
+    if effective.rlim_cur != value {
+        return Err(not_applied(shown));
+    }
+    Ok(shown)
+}
+#[cfg(not(target_os = "linux"))]
+fn get_rlimit(_pid: u32, _resource: Resource, _value: u64) -> io::Result<String> {
+    Err(unsupported())
+}
+
+/// the `tool-<name>` group next to the tool's own cgroup. cgroup v2 hands controllers
+/// only to the children of a group without processes, which the tool's group is not
+#[cfg(target_os = "linux")]
+fn group_dir(name: &str) -> io::Result<PathBuf> {
+    let own = std::fs::read_to_string("/proc/self/cgroup")?;
+    let path = own
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .ok_or_else(|| io::Error::other("cgroup v2 is not mounted"))?;
+    // the root group has no such rule, a tool living there puts its groups below it
+    let parent = match path.trim_matches('/').rsplit_once('/') {
+        Some((parent, _)) => std::path::Path::new(CGROUP_ROOT).join(parent),
+        None => PathBuf::from(CGROUP_ROOT),
+    };
+    Ok(parent.join(format!("tool-{}", name)))
+}
+
+/// creates the group of `name` with `memory.max` set, its `cgroup.procs` for the child
+#[cfg(target_os = "linux")]
+fn make_group(name: &str, bytes: u64) -> io::Result<PathBuf> {
+    use std::fs;
+    let group = group_dir(name)?;
+    let parent = group.parent().unwrap_or(&group);
+    // fails when the parent is not delegated to the user, memory.max is missing then
+    let _ = fs::write(parent.join("cgroup.subtree_control"), "+memory");
+    fs::create_dir_all(&group)?;
+    if let Err(err) = fs::write(group.join("memory.max"), bytes.to_string()) {
+        let _ = fs::remove_dir(&group);
+        return Err(io::Error::new(
+            err.kind(),
+            format!("{} ({})", err, group.display()),
+        ));
+    }
+    Ok(group.join("cgroup.procs"))
+}
+#[cfg(not(target_os = "linux"))]
+fn make_group(_name: &str, _bytes: u64) -> io::Result<PathBuf> {
+    Err(unsupported())
+}
+
+#[cfg(target_os = "linux")]
+fn get_memory(pid: u32, name: &str) -> io::Result<String> {
+    use std::fs;
+    let group = group_dir(name)?;
+    let member = fs::read_to_string(format!("/proc/{}/cgroup", pid))?;
+    let inside = member
+        .lines()
+        .filter_map(|line| line.strip_prefix("0::"))
+        .any(|path| std::path::Path::new(CGROUP_ROOT).join(path.trim_start_matches('/')) == group);
+    if !inside {
+        return Err(not_applied(format!("outside {}", group.display())));
+    }
+    let effective = fs::read_to_string(group.join("memory.max"))?;
+    let effective = match effective.trim().parse::<u64>() {
+        Ok(bytes) => format_bytes(bytes),
+        Err(_) => effective.trim().to_string(),
+    };
+    Ok(format!("{} ({})", effective, group.display()))
+}
+#[cfg(not(target_os = "linux"))]
+fn get_memory(_pid: u32, _name: &str) -> io::Result<String> {
+    Err(unsupported())
+}
+
+/// removes the group of `name` once its processes are gone, a group still in use stays
+pub fn release(name: &str) {
+    #[cfg(target_os = "linux")]
+    if let Ok(group) = group_dir(name) {
+        let _ = std::fs::remove_dir(group);
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = name;
+}
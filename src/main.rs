@@ -1,16 +1,36 @@
+use alerts::{Alerts, AlertsPanel, Severity};
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
 use crossterm::ExecutableCommand;
+use std::collections::{HashMap, VecDeque};
 use std::env;
-use std::io::{stdout, Result};
+use std::future::Future;
+use std::io::{stdout, Read, Result, Write};
 use std::process::Stdio;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use tokio::task;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::{OwnedSemaphorePermit, RwLock, Semaphore, SemaphorePermit};
+use tokio::task::{self, JoinHandle};
 
-use command::{CmdInfo, Config, MenuCommand};
-use crossterm::event::{Event, EventStream, KeyCode, KeyEventKind};
+use command::{
+    CmdInfo, Config, DirtyPolicy, GitAction, Hook, Hooks, LogPanel, MenuCommand, OnExit,
+    QuickAction, Retry, ServiceAction, StdinMode, Step, StepKind, ThresholdAction, TriggerAction,
+};
+use control::{Listener, Reply, Request};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use dialog::Dialog;
+use editor::CommandEditor;
+use explorer::{format_bytes, format_time, Explorer};
+use form::{ArgsForm, FormEvent};
+use history::{HistoryPanel, Outcome, Run, Trigger};
+use input::{InputEvent, LineInput};
+use jobs::{Jobs, JobsPanel, Ticket};
+use limits::Setup;
+use monitor::{Sampler, Usage};
+use nodes::{NodeRepo, NodesPanel, RepoState};
+use params::{ParamPrompt, PromptEvent, Recent};
+use pipeline::{Pipeline, StepStatus};
+use pty::{LineSplitter, PtySession};
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Alignment, Constraint, Layout, Rect};
 use ratatui::style::palette::tailwind::SLATE;
@@ -18,31 +38,290 @@ use ratatui::style::{Color, Modifier, Style, Stylize};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{
     Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState, Padding, Paragraph,
-    Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget, Wrap,
+    Scrollbar, ScrollbarOrientation, ScrollbarState, Sparkline, StatefulWidget, Widget, Wrap,
 };
 use ratatui::DefaultTerminal;
-use std::time::Duration;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
+use snapshot::{PackagesPanel, Snapshot};
+use std::time::{Duration, Instant};
+use sysinfo::Signal;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{ChildStdin, Command};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::sync::watch;
 use tokio_stream::wrappers::LinesStream;
 use tokio_stream::StreamExt;
+use triggers::Armed;
+mod alerts;
 mod command;
+mod control;
+mod crash;
+mod dialog;
+mod editor;
+mod explorer;
+mod form;
+mod git;
+mod history;
+mod input;
+mod jobs;
+mod limits;
+mod monitor;
+mod nodes;
+mod params;
+mod pipeline;
+mod pty;
+mod snapshot;
+mod triggers;
+
+/// how long a dependent waits for its dependency to report ready
+const READY_TIMEOUT: Duration = Duration::from_secs(120);
+/// how long the border of a panel blinks after a `flash` trigger
+const FLASH: Duration = Duration::from_secs(3);
+/// how long a service gets to exit after the polite signal before it is killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+/// how long a killed service gets to be reaped
+const KILL_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Status {
     Idle,
+    Starting,
     Run,
+    Paused,
+}
+
+/// what the log panel input sends to a child's stdin
+#[derive(Debug)]
+enum StdinMsg {
+    Line(String),
+    Eof,
+}
+
+#[derive(Debug, Default)]
+struct ServiceState {
+    pid: Option<u32>,
+    isrunning: bool,
+    ready: bool,
+    /// stopped with SIGSTOP until resumed
+    paused: bool,
+    /// a restart is waiting for the old process to exit
+    restarting: bool,
+    /// the tool asked the process to exit, so its exit is no crash
+    stopping: bool,
+    /// hooks of the running instance, work dirs filled in
+    hooks: Hooks,
+    logs: Vec<String>,
+    /// stdin of the latest piped process in this panel
+    stdin: Option<UnboundedSender<StdinMsg>>,
+    /// terminal of a running `pty` command, shown instead of the log lines
+    pty: Option<Arc<PtySession>>,
+    usage: Usage,
+    /// limits that took effect on the running process
+    limits: Vec<String>,
+    /// output triggers of the running instance
+    triggers: Vec<Armed>,
+    /// actions set off by output lines, run on the next tick
+    fired: Vec<TriggerAction>,
+    /// when a `flash` trigger last went off
+    flashed: Option<Instant>,
+}
+impl ServiceState {
+    /// frees the slot of the exited process, true when it exited without being asked to
+    fn exited(&mut self) -> bool {
+        self.pid = None;
+        self.isrunning = false;
+        self.ready = false;
+        self.paused = false;
+        self.limits.clear();
+        !std::mem::take(&mut self.stopping)
+    }
+    /// marks ready and flashes right away, leaves the rest to `App::run_triggers`
+    fn check_triggers(&mut self, line: &str) {
+        for action in triggers::check(&mut self.triggers, line) {
+            match action {
+                TriggerAction::Ready => self.ready = true,
+                TriggerAction::Flash => self.flashed = Some(Instant::now()),
+                action => self.fired.push(action),
+            }
+        }
+    }
+    /// true while the border should be drawn highlighted, blinking for `FLASH`
+    fn flashing(&self) -> bool {
+        self.flashed
+            .map(|at| at.elapsed())
+            .is_some_and(|since| since < FLASH && since.as_millis() / 250 % 2 == 0)
+    }
+    fn push(&mut self, line: String, limit: usize) {
+        self.logs.push(line);
+        if self.logs.len() > limit {
+            self.logs.remove(0);
+        }
+    }
+    fn status(&self) -> Status {
+        match (self.isrunning, self.ready) {
+            (false, _) => Status::Idle,
+            (true, _) if self.paused => Status::Paused,
+            (true, false) => Status::Starting,
+            (true, true) => Status::Run,
+        }
+    }
 }
 
 #[derive(Debug, Default)]
 struct LogLists {
-    comfyui_pid: Option<u32>,
-    cron_pid: Option<u32>,
-    comfyui_isrunning: bool,
-    cron_isrunning: bool,
-    comfyui_logs: Vec<String>,
-    cron_logs: Vec<String>,
+    comfyui: ServiceState,
+    cron: ServiceState,
     debug_logs: Vec<String>,
+    /// latest run of every command with `steps`
+    pipelines: HashMap<String, Pipeline>,
+    /// the last git update, kept so it can be undone
+    rollback: Option<Rollback>,
+    /// checkouts below `custom_nodes.dir`
+    nodes: Vec<NodeRepo>,
+    /// launches that have not ended yet, finished ones go to the history file
+    runs: Vec<Run>,
+    /// one-shot commands and quick actions, queued, running and recently ended
+    jobs: Jobs,
+    /// toasts and the alerts panel
+    alerts: Alerts,
+}
+
+/// stops a one-shot command that runs past its `timeout` or whose job is cancelled,
+/// politely first
+#[derive(Debug)]
+struct Watchdog {
+    task: JoinHandle<()>,
+    fired: Arc<OnceLock<Outcome>>,
+}
+impl Watchdog {
+    fn start(
+        logs: &Arc<RwLock<LogLists>>,
+        panel: LogPanel,
+        limit: usize,
+        pid: Option<u32>,
+        timeout: Option<u64>,
+        job: Option<&Ticket>,
+    ) -> Option<Watchdog> {
+        let pid = pid?;
+        if timeout.is_none() && job.is_none() {
+            return None;
+        }
+        let fired = Arc::new(OnceLock::new());
+        let outcome = Arc::clone(&fired);
+        let logs = Arc::clone(logs);
+        let job = job.cloned();
+        let task = tokio::spawn(async move {
+            if let Some(job) = &job {
+                if let Some(job) = logs.write().await.jobs.get_mut(job.id) {
+                    job.pid = Some(pid);
+                }
+            }
+            let timed_out = async {
+                match timeout {
+                    Some(secs) => tokio::time::sleep(Duration::from_secs(secs)).await,
+                    None => std::future::pending().await,
+                }
+            };
+            let cancelled = async {
+                match &job {
+                    Some(job) => job.cancelled().await,
+                    None => std::future::pending().await,
+                }
+            };
+            let line = tokio::select! {
+                _ = timed_out => {
+                    let secs = timeout.unwrap_or_default();
+                    let _ = outcome.set(Outcome::TimedOut(secs));
+                    format!("Timed out after {}s, sending exit signal :{}", secs, pid)
+                }
+                _ = cancelled => {
+                    let _ = outcome.set(Outcome::Cancelled);
+                    format!("Cancelled, sending exit signal :{}", pid)
+                }
+            };
+            logs.write().await.push_panel(panel, line, limit);
+            if task::block_in_place(|| explorer::signal_tree(pid, Signal::Term)).is_ok() {
+                tokio::time::sleep(STOP_TIMEOUT).await;
+            }
+            logs.write()
+                .await
+                .push_panel(panel, format!("Still running, killing :{}", pid), limit);
+            let _ = task::block_in_place(|| explorer::signal_tree(pid, Signal::Kill));
+        });
+        Some(Watchdog { task, fired })
+    }
+    /// called once the process exited, what ended it when it was the watchdog
+    fn stop(self) -> Option<Outcome> {
+        self.task.abort();
+        self.fired.get().cloned()
+    }
+}
+
+/// how an attempt's child ended
+#[derive(Debug)]
+struct Ended {
+    pid: Option<u32>,
+    /// history entry of the attempt
+    run: u64,
+    /// how the exit status prints and its code, `None` when a signal ended it
+    status: std::result::Result<(String, Option<i32>), String>,
+    /// set when the watchdog stopped the child
+    stopped: Option<Outcome>,
+    tail: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Rollback {
+    work_dir: String,
+    previous: String,
+    current: String,
+    panel: LogPanel,
+    /// service that did not start after the update
+    failed: Option<MenuCommand>,
+    offered: bool,
+}
+impl LogLists {
+    /// lists `cmdinfo` as running in the history panel
+    fn begin_run(&mut self, cmdinfo: &CmdInfo, trigger: Trigger) -> u64 {
+        let run = Run::start(cmdinfo, trigger);
+        let id = run.id;
+        self.runs.push(run);
+        id
+    }
+    /// takes the run `id` off the running ones, ended, for `App::save_run`
+    fn end_run(&mut self, id: u64, outcome: Outcome, tail: Vec<String>) -> Option<Run> {
+        let i = self.runs.iter().position(|r| r.id == id)?;
+        Some(self.runs.remove(i).finish(outcome, tail))
+    }
+    /// service panels keep the last `limit` lines, the debug console keeps everything
+    fn push_panel(&mut self, panel: LogPanel, line: String, limit: usize) {
+        let lines = self.panel_mut(panel);
+        lines.push(line);
+        if panel != LogPanel::Debug && lines.len() > limit {
+            lines.remove(0);
+        }
+    }
+    fn panel_mut(&mut self, panel: LogPanel) -> &mut Vec<String> {
+        match panel {
+            LogPanel::Debug => &mut self.debug_logs,
+            LogPanel::Comfyui => &mut self.comfyui.logs,
+            LogPanel::Cron => &mut self.cron.logs,
+        }
+    }
+    fn node_mut(&mut self, path: &str) -> Option<&mut NodeRepo> {
+        self.nodes.iter_mut().find(|r| r.path == path)
+    }
+    fn service(&self, mode: CommandMode) -> &ServiceState {
+        match mode {
+            CommandMode::Comfyui => &self.comfyui,
+            CommandMode::Cron => &self.cron,
+        }
+    }
+    fn service_mut(&mut self, mode: CommandMode) -> &mut ServiceState {
+        match mode {
+            CommandMode::Comfyui => &mut self.comfyui,
+            CommandMode::Cron => &mut self.cron,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -83,25 +362,98 @@ impl FromIterator<(MenuCommand, &'static str, &'static str, Status)> for MenuLis
 
 impl From<&MenuInfo> for ListItem<'_> {
     fn from(mn: &MenuInfo) -> Self {
-        let line = Line::styled(format!("{}", mn.title), TEXT_FG_COLOR);
+        let line = Line::styled(mn.title.clone(), TEXT_FG_COLOR);
         ListItem::new(line)
     }
 }
 const NORMAL_ROW_BG: Color = SLATE.c950;
-const ALT_ROW_BG_COLOR: Color = SLATE.c900;
 const SELECTED_STYLE: Style = Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD);
 const TEXT_FG_COLOR: Color = SLATE.c200;
 const fn state_color(i: usize) -> Color {
-    if i % 2 == 0 {
+    if i.is_multiple_of(2) {
         NORMAL_ROW_BG
     } else {
         Color::Red
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum CommandMode {
-    COMFYUI,
-    CRON,
+    Comfyui,
+    Cron,
+}
+impl CommandMode {
+    fn of(command: MenuCommand) -> Option<Self> {
+        match command {
+            MenuCommand::ComfyRun
+            | MenuCommand::ComfyUpdate
+            | MenuCommand::ComfyKill
+            | MenuCommand::ComfyRestart
+            | MenuCommand::ComfyPause
+            | MenuCommand::ComfySignal => Some(CommandMode::Comfyui),
+            MenuCommand::CronRun
+            | MenuCommand::CronKill
+            | MenuCommand::CronRestart
+            | MenuCommand::CronPause
+            | MenuCommand::CronSignal => Some(CommandMode::Cron),
+            _ => None,
+        }
+    }
+    /// the panel an entry's output goes to, `Custom` entries pick theirs
+    fn of_entry(cmd: &CmdInfo) -> Option<Self> {
+        match (cmd.command, cmd.panel) {
+            (MenuCommand::Custom, Some(LogPanel::Cron)) => Some(CommandMode::Cron),
+            (MenuCommand::Custom, _) => Some(CommandMode::Comfyui),
+            (command, _) => CommandMode::of(command),
+        }
+    }
+    /// the service whose output goes to this panel
+    fn service(&self) -> MenuCommand {
+        match self {
+            CommandMode::Comfyui => MenuCommand::ComfyRun,
+            CommandMode::Cron => MenuCommand::CronRun,
+        }
+    }
+    fn panel(&self) -> LogPanel {
+        match self {
+            CommandMode::Comfyui => LogPanel::Comfyui,
+            CommandMode::Cron => LogPanel::Cron,
+        }
+    }
+}
+
+/// deferred choices offered by the confirmation dialog
+#[derive(Debug, Clone)]
+enum DialogAction {
+    Stop(MenuCommand),
+    StopWithDependents(MenuCommand),
+    QuitStop,
+    QuitDetach,
+    Signal(u32, Signal),
+    /// the entry key of the pipeline
+    CancelPipeline(String),
+    Rollback,
+    RestorePackages(Snapshot),
+    SaveForm,
+    DiscardForm,
+    SaveCommand,
+    DiscardCommand,
+    DeleteCommand(usize),
+}
+
+/// what the right hand side shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum View {
+    Logs,
+    Debug,
+    Processes,
+    Nodes,
+    Packages,
+    Args,
+    Command,
+    History,
+    Jobs,
+    Alerts,
 }
 #[derive(Debug, Clone, PartialEq)]
 enum ActivePanel {
@@ -111,6 +463,13 @@ enum ActivePanel {
     DebugLog,
 }
 impl ActivePanel {
+    fn mode(&self) -> Option<CommandMode> {
+        match self {
+            ActivePanel::ComfyLog => Some(CommandMode::Comfyui),
+            ActivePanel::CronLog => Some(CommandMode::Cron),
+            _ => None,
+        }
+    }
     pub fn next(&self) -> Self {
         match self {
             ActivePanel::Menu => ActivePanel::ComfyLog,
@@ -120,44 +479,120 @@ impl ActivePanel {
         }
     }
 }
-#[derive(Debug, Clone)]
+/// command line flags
+#[derive(Debug, Clone, Default)]
+struct Args {
+    no_autostart: bool,
+    /// `tool history [name]` prints the run history instead of starting the ui
+    history: Option<Option<String>>,
+    /// `tool restart <service>` asks the ui running in this folder to restart a service
+    restart: Option<String>,
+}
+impl Args {
+    fn parse() -> Self {
+        let mut args = Args::default();
+        let mut rest = env::args().skip(1);
+        while let Some(arg) = rest.next() {
+            match arg.as_str() {
+                "--no-autostart" => args.no_autostart = true,
+                "history" if args.history.is_none() => args.history = Some(rest.next()),
+                "restart" if args.restart.is_none() => match rest.next() {
+                    Some(service) => args.restart = Some(service),
+                    None => Args::usage("restart needs a service"),
+                },
+                _ => Args::usage(&format!("unknown argument: {}", arg)),
+            }
+        }
+        args
+    }
+    fn usage(problem: &str) -> ! {
+        eprintln!("{}", problem);
+        eprintln!("usage: tool [--no-autostart] | tool history [name] | tool restart <service>");
+        std::process::exit(2);
+    }
+}
+
+#[derive(Debug)]
 struct App {
     config: Config,
+    /// `config` for background tasks that outlive a change of it
+    shared_config: watch::Sender<Config>,
+    args: Args,
     selected_config: Option<CmdInfo>,
     should_exit: bool,
     menu_list: MenuList,
     logs: Arc<RwLock<LogLists>>,
-    show_debugconsole: bool,
+    view: View,
+    explorer: Explorer,
+    nodes: NodesPanel,
+    packages: PackagesPanel,
+    history: HistoryPanel,
+    jobs: JobsPanel,
+    alerts: AlertsPanel,
+    /// args of the command opened with `a`
+    form: Option<ArgsForm>,
+    /// menu entry being added or edited
+    editor: Option<CommandEditor>,
+    /// values asked for before a command with params runs
+    prompt: Option<ParamPrompt>,
+    recent: Recent,
     comfylog_scrollbar_state: ScrollbarState,
     cronlog_scrollbar_state: ScrollbarState,
     comfylog_scroll: usize,
     cronlog_scroll: usize,
     active_panel: ActivePanel,
+    dialog: Option<Dialog<DialogAction>>,
+    shutdown: Option<Arc<JoinHandle<()>>>,
+    /// log panel whose process receives typed lines
+    attached: Option<CommandMode>,
+    input: LineInput,
+    /// log panels blinking because of a `flash` trigger
+    flashing: Vec<ActivePanel>,
 }
 
 impl App {
-    fn new() -> Self {
-        let config = Config::new();
+    fn new(config: Config, args: Args) -> Self {
         let items = config
             .commands
             .iter()
-            .map(|c| MenuInfo::new(c.command.clone(), &c.name, &c.desc, Status::Idle))
+            .map(|c| MenuInfo::new(c.command, &c.name, &c.desc, Status::Idle))
             .collect::<Vec<MenuInfo>>();
+        let logs = LogLists {
+            jobs: Jobs::new(config.max_jobs),
+            ..LogLists::default()
+        };
 
         Self {
+            shared_config: watch::Sender::new(config.clone()),
             config,
+            args,
             should_exit: false,
             selected_config: None,
-            logs: Arc::new(RwLock::new(LogLists::default())),
+            logs: Arc::new(RwLock::new(logs)),
 
-            show_debugconsole: false,
+            view: View::Logs,
+            explorer: Explorer::default(),
+            nodes: NodesPanel::default(),
+            packages: PackagesPanel::default(),
+            history: HistoryPanel::default(),
+            jobs: JobsPanel::default(),
+            alerts: AlertsPanel::default(),
+            form: None,
+            editor: None,
+            prompt: None,
+            recent: Recent::load(),
             comfylog_scrollbar_state: ScrollbarState::new(10),
             cronlog_scrollbar_state: ScrollbarState::new(10),
             comfylog_scroll: 0,
             cronlog_scroll: 0,
             active_panel: ActivePanel::Menu,
+            dialog: None,
+            shutdown: None,
+            attached: None,
+            input: LineInput::default(),
+            flashing: Vec::new(),
             menu_list: MenuList {
-                items: items,
+                items,
                 state: ListState::default(),
             },
         }
@@ -167,18 +602,109 @@ impl App {
         let period = Duration::from_secs_f32(1.0 / self.config.fps);
         let mut interval = tokio::time::interval(period);
         let mut events = EventStream::new();
+        let terminate = termination_signal();
+        tokio::pin!(terminate);
+        let mut terminated = false;
+        supervise(
+            &self.logs,
+            "monitor",
+            App::monitor(Arc::clone(&self.logs), self.shared_config.subscribe()),
+        );
+        if !self.args.no_autostart {
+            self.autostart();
+        }
+        let (requests_tx, mut requests) = mpsc::unbounded_channel();
+        match Listener::bind().await {
+            Ok(listener) => {
+                supervise(&self.logs, "control", listener.serve(requests_tx));
+            }
+            Err(err) => self.logs.write().await.debug_logs.push(format!(
+                "[warn] no control listener, tool restart will not reach this ui :{}",
+                err
+            )),
+        }
 
         while !self.should_exit {
             tokio::select! {
-                _ = interval.tick() => {  terminal.draw(|frame| {
+                _ = interval.tick() => {
+                    self.offer_rollback().await;
+                    self.run_triggers().await;
+                    if self.view == View::Alerts {
+                        self.logs.write().await.alerts.mark_read();
+                    }
+                    terminal.draw(|frame| {
                     frame.render_widget(Clear,frame.area());
                     frame.render_widget(&mut self, frame.area())})?;},
                 Some(Ok(event)) = events.next() => self.handle_events(&event,&mut terminal).await,
+                Some((request, reply)) = requests.recv() => self.handle_request(request, reply).await,
+                signal = &mut terminate, if !terminated => {
+                    terminated = true;
+                    if signal.is_ok() {
+                        // nobody is at the keyboard to answer a dialog
+                        let policy = match self.config.on_exit {
+                            OnExit::Ask => OnExit::Stop,
+                            policy => policy,
+                        };
+                        self.dialog = None;
+                        self.request_exit(policy).await;
+                    }
+                }
+            }
+            if self.shutdown.as_ref().is_some_and(|h| h.is_finished()) {
+                self.should_exit = true;
             }
         }
+        control::remove();
 
         Ok(())
     }
+    /// a request from another `tool` process
+    async fn handle_request(&mut self, request: Request, reply: Reply) {
+        match request {
+            Request::Restart(name) => {
+                let service = self
+                    .config
+                    .commands
+                    .iter()
+                    .filter(|c| c.command.is_service())
+                    .find(|c| format!("{:?}", c.command) == name || c.name.trim() == name)
+                    .map(|c| c.command);
+                let Some(service) = service else {
+                    reply
+                        .send(false, &format!("no service named {}", name))
+                        .await;
+                    return;
+                };
+                if let Some(mode) = CommandMode::of(service) {
+                    let line = format!("Restart of {:?} asked for from the command line", service);
+                    self.logs
+                        .write()
+                        .await
+                        .service_mut(mode)
+                        .push(line, self.config.limit);
+                }
+                let task =
+                    App::restart_service(Arc::clone(&self.logs), self.config.clone(), service);
+                supervise(&self.logs, "restart_service", task);
+                reply.send(true, &format!("restarting {:?}", service)).await;
+            }
+        }
+    }
+    /// starts every service flagged `autostart`, dependencies are pulled in by `start_service`
+    fn autostart(&self) {
+        for svc in self.config.service_order().unwrap_or_default() {
+            let Some(info) = self.config.find(svc).filter(|c| c.autostart) else {
+                continue;
+            };
+            let delay = Duration::from_secs(info.autostart_delay);
+            let logs = Arc::clone(&self.logs);
+            let config = self.config.clone();
+            supervise(&self.logs, "autostart", async move {
+                tokio::time::sleep(delay).await;
+                App::start_service(logs, config, svc, Trigger::Scheduled).await;
+            });
+        }
+    }
     fn trim_line(line: String) -> String {
         line.trim_end().to_string()
         // if input.len() > 50 {
@@ -190,211 +716,2088 @@ impl App {
         // }
     }
 
-    fn run_command(self, mode: CommandMode, menucommand: MenuCommand) {
-        let logs = Arc::clone(&self.logs);
-        let cmdifo = match &self.selected_config {
-            Some(cmd) => cmd.clone(),
-            None => panic!("nothing selected"),
+    /// runs `cmdifo` in the panel of `mode`, a one-shot command goes through the jobs
+    /// panel and is tried again as often as its `retry` allows
+    fn run_command(
+        logs: Arc<RwLock<LogLists>>,
+        limit: usize,
+        cmdifo: CmdInfo,
+        mode: CommandMode,
+        trigger: Trigger,
+    ) {
+        supervise(&Arc::clone(&logs), "run_command", async move {
+            if cmdifo.command.is_service() {
+                App::run_attempt(&logs, limit, &cmdifo, mode, trigger, None).await;
+                return;
+            }
+            let key = cmdifo.key();
+            let queued = App::queue_job(&logs, mode.panel(), limit, &key, cmdifo.name.trim()).await;
+            let Some((job, _slot)) = queued else {
+                return;
+            };
+            let outcome = App::run_attempts(&logs, limit, &cmdifo, mode, trigger, &job).await;
+            let mut logs = logs.write().await;
+            if App::alarming(&outcome) {
+                let line = format!("{} failed :{}", cmdifo.name.trim(), outcome.label());
+                logs.alerts.raise(Severity::Error, line);
+            }
+            logs.jobs.finish(job.id, outcome);
+        });
+    }
+    /// a failure nobody asked for, cancels are left out
+    fn alarming(outcome: &Outcome) -> bool {
+        !outcome.succeeded() && *outcome != Outcome::Cancelled
+    }
+    /// lists a job in the jobs panel and waits for a free slot, `None` when a job of the
+    /// same entry is queued or running already or this one got cancelled while queued
+    async fn queue_job(
+        logs: &Arc<RwLock<LogLists>>,
+        panel: LogPanel,
+        limit: usize,
+        key: &str,
+        name: &str,
+    ) -> Option<(Ticket, OwnedSemaphorePermit)> {
+        let (job, slots) = {
+            let mut logs = logs.write().await;
+            let Some((job, slots)) = logs.jobs.add(key, name) else {
+                logs.push_panel(
+                    panel,
+                    format!("{} is already queued or running", name),
+                    limit,
+                );
+                return None;
+            };
+            if slots.available_permits() == 0 {
+                let line = format!(
+                    "Queued {}, {} of {} jobs running",
+                    name,
+                    logs.jobs.running(),
+                    logs.jobs.max
+                );
+                logs.push_panel(panel, line, limit);
+            }
+            (job, slots)
         };
-
-        tokio::spawn(async move {
-            let mut cmd = Command::new(cmdifo.exe_path)
+        let slot = tokio::select! {
+            slot = slots.acquire_owned() => slot.ok(),
+            _ = job.cancelled() => None,
+        };
+        let mut logs = logs.write().await;
+        let Some(slot) = slot else {
+            logs.jobs.finish(job.id, Outcome::Cancelled);
+            logs.push_panel(panel, format!("{} cancelled while queued", name), limit);
+            return None;
+        };
+        logs.jobs.start(job.id);
+        Some((job, slot))
+    }
+    /// runs `cmdifo` until it succeeds, its `retry` gives up or the job is cancelled
+    async fn run_attempts(
+        logs: &Arc<RwLock<LogLists>>,
+        limit: usize,
+        cmdifo: &CmdInfo,
+        mode: CommandMode,
+        trigger: Trigger,
+        job: &Ticket,
+    ) -> Outcome {
+        let push = |logs: &mut LogLists, line: String| logs.service_mut(mode).push(line, limit);
+        App::retrying(
+            logs,
+            cmdifo.name.trim(),
+            cmdifo.retry.as_ref(),
+            Some(job),
+            push,
+            |attempt| {
+                let trigger = if attempt == 0 {
+                    trigger
+                } else {
+                    Trigger::Retry
+                };
+                App::run_attempt(logs, limit, cmdifo, mode, trigger, Some(job))
+            },
+        )
+        .await
+    }
+    /// runs `attempt` until it succeeds, `retry` gives up or `job` is cancelled between
+    /// attempts, `attempt` gets the number of attempts before it, progress goes to `push`
+    async fn retrying<F: Future<Output = Outcome>>(
+        logs: &Arc<RwLock<LogLists>>,
+        name: &str,
+        retry: Option<&Retry>,
+        job: Option<&Ticket>,
+        push: impl Fn(&mut LogLists, String),
+        mut attempt: impl FnMut(u32) -> F,
+    ) -> Outcome {
+        let mut tried = 0;
+        loop {
+            let outcome = attempt(tried).await;
+            let retry = retry.filter(|retry| tried < retry.count && outcome.retryable(retry));
+            let Some(retry) = retry else {
+                if tried > 0 {
+                    let line = if outcome.succeeded() {
+                        format!("{} succeeded on attempt {}", name, tried + 1)
+                    } else {
+                        format!(
+                            "{} gave up after {} attempts :{}",
+                            name,
+                            tried + 1,
+                            outcome.label()
+                        )
+                    };
+                    push(&mut *logs.write().await, line);
+                }
+                return outcome;
+            };
+            tried += 1;
+            let line = format!(
+                "Attempt {} of {} :{}, trying again in {}s",
+                tried,
+                retry.count + 1,
+                outcome.label(),
+                retry.delay
+            );
+            push(&mut *logs.write().await, line);
+            let cancelled = async {
+                match job {
+                    Some(job) => job.cancelled().await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(retry.delay)) => {}
+                _ = cancelled => {
+                    let line = format!("{} cancelled before attempt {}", name, tried + 1);
+                    push(&mut *logs.write().await, line);
+                    return Outcome::Cancelled;
+                }
+            }
+        }
+    }
+    async fn run_attempt(
+        logs: &Arc<RwLock<LogLists>>,
+        limit: usize,
+        cmdifo: &CmdInfo,
+        mode: CommandMode,
+        trigger: Trigger,
+        job: Option<&Ticket>,
+    ) -> Outcome {
+        if cmdifo.pty {
+            App::run_pty(logs, limit, cmdifo, mode, trigger, job).await
+        } else {
+            App::run_piped(logs, limit, cmdifo, mode, trigger, job).await
+        }
+    }
+    /// one attempt with piped output, ended early by the command's `timeout` or a cancel
+    async fn run_piped(
+        logs: &Arc<RwLock<LogLists>>,
+        limit: usize,
+        cmdifo: &CmdInfo,
+        mode: CommandMode,
+        trigger: Trigger,
+        job: Option<&Ticket>,
+    ) -> Outcome {
+        let service = cmdifo.command.is_service();
+        let run = logs.write().await.begin_run(cmdifo, trigger);
+        let stdin = match &cmdifo.stdin {
+            StdinMode::Null => Ok(Stdio::null()),
+            StdinMode::Piped => Ok(Stdio::piped()),
+            StdinMode::File(path) => std::fs::File::open(path).map(Stdio::from),
+        };
+        let (setup, failed) = Setup::new(&App::limits_name(cmdifo), &cmdifo.limits);
+        let spawned = stdin.and_then(|stdin| {
+            let mut command = Command::new(&cmdifo.exe_path);
+            command
                 .env("PYTHONUNBUFFERED", "1")
-                .current_dir(cmdifo.work_dir)
-                .args(cmdifo.args)
+                .current_dir(&cmdifo.work_dir)
+                .args(&cmdifo.args)
+                .stdin(stdin)
                 .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-                .expect("Failed to execute command");
+                .stderr(Stdio::piped());
+            #[cfg(windows)]
+            if let Some(nice) = cmdifo.limits.nice {
+                command.creation_flags(limits::priority_class(nice));
+            }
+            #[cfg(unix)]
+            setup.install(&mut command);
+            command.spawn()
+        });
+        let mut cmd = match spawned {
+            Ok(cmd) => cmd,
+            Err(err) => {
+                let err = format!("Failed to execute {} :{}", cmdifo.exe_path, err);
+                let outcome = Outcome::Failed(err.clone());
+                let run = {
+                    let mut logs = logs.write().await;
+                    let state = logs.service_mut(mode);
+                    if service {
+                        state.isrunning = false;
+                    }
+                    state.push(err, limit);
+                    logs.end_run(run, outcome.clone(), Vec::new())
+                };
+                App::save_run(logs, run).await;
+                return outcome;
+            }
+        };
+
+        let pid = cmd.id();
+        let applied = App::applied_limits(cmdifo, &setup, failed, pid, false);
+        let watchdog = Watchdog::start(logs, mode.panel(), limit, pid, cmdifo.timeout, job);
+        let stdin = cmd.stdin.take().map(|child_stdin| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            supervise(logs, "write_stdin", App::write_stdin(child_stdin, rx));
+            tx
+        });
+        {
+            let mut logs = logs.write().await;
+            let state = logs.service_mut(mode);
+            if service {
+                state.pid = pid;
+                state.ready = cmdifo.ready_pattern.is_empty();
+                state.limits = applied.clone();
+            }
+            if !applied.is_empty() {
+                state.push(format!("Limits :{}", applied.join(", ")), limit);
+            }
+            if stdin.is_some() {
+                state.stdin = stdin.clone();
+            }
+        }
+
+        let stdout = cmd.stdout.take().unwrap();
+        let stderr = cmd.stderr.take().unwrap();
+
+        let stdout = LinesStream::new(BufReader::new(stdout).lines());
+        let stderr = LinesStream::new(BufReader::new(stderr).lines());
+        let mut merged = StreamExt::merge(stdout, stderr);
+        let mut tail = VecDeque::new();
+        while let Some(line) = merged.next().await {
+            let mut logs = logs.write().await;
+            let line = match line {
+                Ok(l) => App::trim_line(l),
+                Err(_) => String::from("----deducted---"),
+            };
+            if tail.len() == history::TAIL {
+                tail.pop_front();
+            }
+            tail.push_back(line.clone());
 
-            let stdout = cmd.stdout.take().unwrap();
-            let stderr = cmd.stderr.take().unwrap();
+            let state = logs.service_mut(mode);
+            if service && !state.ready && line.contains(&cmdifo.ready_pattern) {
+                state.ready = true;
+            }
+            if service {
+                state.check_triggers(&line);
+            }
+            state.push(line, limit);
+        }
 
-            let stdout = LinesStream::new(BufReader::new(stdout).lines());
-            let stderr = LinesStream::new(BufReader::new(stderr).lines());
-            let mut merged = StreamExt::merge(stdout, stderr);
-            while let Some(line) = merged.next().await {
+        let status = cmd
+            .wait()
+            .await
+            .map(|status| (status.to_string(), status.code()))
+            .map_err(|err| err.to_string());
+        if setup.is_grouped() {
+            limits::release(&App::limits_name(cmdifo));
+        }
+        let stopped = watchdog.and_then(Watchdog::stop);
+        let ended = Ended {
+            pid,
+            run,
+            status,
+            stopped,
+            tail: tail.into(),
+        };
+        App::end_attempt(logs, limit, cmdifo, mode, ended, |state| {
+            if let (Some(ours), Some(current)) = (&stdin, &state.stdin) {
+                if ours.same_channel(current) {
+                    state.stdin = None;
+                }
+            }
+        })
+        .await
+    }
+    /// frees the slot of the exited child, records how it ended and reports a crash,
+    /// `detach` drops what the child left in the panel state
+    async fn end_attempt(
+        logs: &Arc<RwLock<LogLists>>,
+        limit: usize,
+        cmdifo: &CmdInfo,
+        mode: CommandMode,
+        ended: Ended,
+        detach: impl FnOnce(&mut ServiceState),
+    ) -> Outcome {
+        let shared = Arc::clone(logs);
+        let mut logs = logs.write().await;
+        let state = logs.service_mut(mode);
+        // a restarted service may already own the slot
+        let ours = cmdifo.command.is_service() && state.pid == ended.pid;
+        let crashed = ours && state.exited();
+        detach(state);
+        let outcome = match ended.status {
+            Ok((status, code)) => {
+                state.push(format!("Exited :{}", status), limit);
+                match ended.stopped {
+                    Some(outcome) => outcome,
+                    None if ours && !crashed => Outcome::Stopped,
+                    None => code.map_or(Outcome::Killed, Outcome::Exit),
+                }
+            }
+            Err(err) => {
+                let err = format!("Failed waiting for exit :{}", err);
+                state.push(err.clone(), limit);
+                Outcome::Failed(err)
+            }
+        };
+        if crashed && !outcome.succeeded() {
+            if let Some(hook) = state.hooks.on_crash.clone() {
+                App::spawn_hook(&shared, mode, limit, "on_crash", hook);
+            }
+            let line = format!("{} crashed :{}", cmdifo.name.trim(), outcome.label());
+            logs.alerts.raise(Severity::Error, line);
+        }
+        let run = logs.end_run(ended.run, outcome.clone(), ended.tail);
+        drop(logs);
+        App::save_run(&shared, run).await;
+        outcome
+    }
+    /// appends an ended run to the history file, off the async threads and the lock
+    async fn save_run(logs: &Arc<RwLock<LogLists>>, run: Option<Run>) {
+        let Some(run) = run else {
+            return;
+        };
+        let name = run.name.clone();
+        let saved = match task::spawn_blocking(move || history::append(&run)).await {
+            Ok(saved) => saved.map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        };
+        if let Err(err) = saved {
+            logs.write().await.debug_logs.push(format!(
+                "[warn] Run of {} not saved to history :{}",
+                name, err
+            ));
+        }
+    }
+
+    /// names the memory group of the entry, a folder name
+    fn limits_name(cmdifo: &CmdInfo) -> String {
+        cmdifo
+            .key()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect()
+    }
+    /// what took effect on the spawned child, after what already failed before the spawn
+    fn applied_limits(
+        cmdifo: &CmdInfo,
+        setup: &Setup,
+        mut failed: Vec<String>,
+        pid: Option<u32>,
+        pty: bool,
+    ) -> Vec<String> {
+        if let Some(pid) = pid.filter(|_| !cmdifo.limits.is_empty()) {
+            let name = App::limits_name(cmdifo);
+            failed.extend(limits::report(pid, &name, &cmdifo.limits, setup, pty));
+        }
+        failed
+    }
+
+    /// one attempt under a pseudo terminal, ended early by the command's `timeout` or a cancel
+    async fn run_pty(
+        logs: &Arc<RwLock<LogLists>>,
+        limit: usize,
+        cmdifo: &CmdInfo,
+        mode: CommandMode,
+        trigger: Trigger,
+        job: Option<&Ticket>,
+    ) -> Outcome {
+        let service = cmdifo.command.is_service();
+        let run = logs.write().await.begin_run(cmdifo, trigger);
+        let (setup, failed) = Setup::new(&App::limits_name(cmdifo), &cmdifo.limits);
+        let (session, mut child, mut reader) = match PtySession::spawn(cmdifo, &setup) {
+            Ok(spawned) => spawned,
+            Err(err) => {
+                let err = format!("Failed to execute {} :{}", cmdifo.exe_path, err);
+                let outcome = Outcome::Failed(err.clone());
+                let run = {
+                    let mut logs = logs.write().await;
+                    let state = logs.service_mut(mode);
+                    if service {
+                        state.isrunning = false;
+                    }
+                    state.push(err, limit);
+                    logs.end_run(run, outcome.clone(), Vec::new())
+                };
+                App::save_run(logs, run).await;
+                return outcome;
+            }
+        };
+        let session = Arc::new(session);
+        let pid = child.process_id();
+        // the launcher limits itself before it becomes the command
+        if let Some(pid) = pid.filter(|_| !setup.is_empty()) {
+            let deadline = tokio::time::Instant::now() + Duration::from_secs(1);
+            while !limits::settled(pid) && tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+        let applied = App::applied_limits(cmdifo, &setup, failed, pid, true);
+        let watchdog = Watchdog::start(logs, mode.panel(), limit, pid, cmdifo.timeout, job);
+        {
+            let mut logs = logs.write().await;
+            let state = logs.service_mut(mode);
+            if service {
+                state.pid = pid;
+                state.ready = cmdifo.ready_pattern.is_empty();
+                state.limits = applied.clone();
+            }
+            if !applied.is_empty() {
+                state.push(format!("Limits :{}", applied.join(", ")), limit);
+            }
+            state.pty = Some(Arc::clone(&session));
+        }
+
+        let reader_logs = Arc::clone(logs);
+        // a process the child left behind can keep the terminal open and the reader blocked
+        // after the drain below gives up, the session must not stay alive with it
+        let reader_session = Arc::downgrade(&session);
+        let pattern = cmdifo.ready_pattern.clone();
+        let reading = task::spawn_blocking(move || {
+            let mut splitter = LineSplitter::default();
+            let mut chunk = [0u8; 4096];
+            let mut tail = VecDeque::new();
+            while let Ok(n) = reader.read(&mut chunk) {
+                if n == 0 {
+                    break;
+                }
+                let Some(session) = reader_session.upgrade() else {
+                    break;
+                };
+                session.process(&chunk[..n]);
+                let lines = splitter.feed(&chunk[..n]);
+                if lines.is_empty() {
+                    continue;
+                }
+                let mut logs = reader_logs.blocking_write();
+                let state = logs.service_mut(mode);
+                for line in lines {
+                    if service && !state.ready && line.contains(&pattern) {
+                        state.ready = true;
+                    }
+                    if service {
+                        state.check_triggers(&line);
+                    }
+                    if tail.len() == history::TAIL {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line.clone());
+                    state.push(line, limit);
+                }
+            }
+            tail
+        });
+
+        let status = match task::spawn_blocking(move || child.wait()).await {
+            Ok(Ok(status)) => Ok((status.to_string(), Some(status.exit_code() as i32))),
+            Ok(Err(err)) => Err(err.to_string()),
+            Err(err) => Err(err.to_string()),
+        };
+        if setup.is_grouped() {
+            limits::release(&App::limits_name(cmdifo));
+        }
+        let stopped = watchdog.and_then(Watchdog::stop);
+        // give the reader a moment to drain what the child wrote last
+        let tail = match tokio::time::timeout(Duration::from_secs(1), reading).await {
+            Ok(Ok(tail)) => tail.into(),
+            _ => Vec::new(),
+        };
+        let ended = Ended {
+            pid,
+            run,
+            status,
+            stopped,
+            tail,
+        };
+        App::end_attempt(logs, limit, cmdifo, mode, ended, |state| {
+            if state.pty.as_ref().is_some_and(|p| Arc::ptr_eq(p, &session)) {
+                state.pty = None;
+            }
+        })
+        .await
+    }
+
+    /// forwards typed lines to the child until EOF is requested or the pipe breaks
+    async fn write_stdin(mut stdin: ChildStdin, mut rx: UnboundedReceiver<StdinMsg>) {
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                StdinMsg::Line(line) => {
+                    let line = format!("{}\n", line);
+                    if stdin.write_all(line.as_bytes()).await.is_err()
+                        || stdin.flush().await.is_err()
+                    {
+                        break;
+                    }
+                }
+                StdinMsg::Eof => break,
+            }
+        }
+    }
+
+    /// sends `msg` to the process attached to `mode`'s log panel and echoes it there
+    async fn send_stdin(&self, mode: CommandMode, msg: StdinMsg) {
+        let mut logs = self.logs.write().await;
+        let state = logs.service_mut(mode);
+        let echo = match &msg {
+            StdinMsg::Line(line) => format!("> {}", line),
+            StdinMsg::Eof => "> ^D".to_string(),
+        };
+        let sent = state.stdin.as_ref().is_some_and(|tx| tx.send(msg).is_ok());
+        if sent {
+            state.push(echo, self.config.limit);
+        } else {
+            state.push(
+                "No process with piped stdin in this panel".to_string(),
+                self.config.limit,
+            );
+        }
+    }
+
+    /// starts `command` after its dependencies are running and ready
+    async fn start_service(
+        logs: Arc<RwLock<LogLists>>,
+        config: Config,
+        command: MenuCommand,
+        trigger: Trigger,
+    ) {
+        let Some(mode) = CommandMode::of(command) else {
+            return;
+        };
+        for svc in config.start_order(command) {
+            let (Some(info), Some(svc_mode)) = (config.find(svc), CommandMode::of(svc)) else {
+                continue;
+            };
+            let started = {
+                let mut logs = logs.write().await;
+                let state = logs.service_mut(svc_mode);
+                if state.isrunning {
+                    false
+                } else {
+                    state.isrunning = true;
+                    state.stopping = false;
+                    true
+                }
+            };
+            if started {
+                if svc != command {
+                    logs.write().await.service_mut(mode).push(
+                        format!("Starting dependency {:?} for {:?}", svc, command),
+                        config.limit,
+                    );
+                }
+                // autostart, start all and restarts use the last values typed
+                let info = params::resolve(info);
+                let hooks = info.hooks.in_dir(&info.work_dir);
+                if let Some(hook) = &hooks.pre_start {
+                    let ok = App::run_hook(&logs, svc_mode, config.limit, "pre_start", hook).await;
+                    if !ok && hook.abort {
+                        let mut logs = logs.write().await;
+                        let state = logs.service_mut(svc_mode);
+                        state.isrunning = false;
+                        state.push(
+                            format!("{:?} not started, pre_start failed", svc),
+                            config.limit,
+                        );
+                        return;
+                    }
+                }
+                let post_start = hooks.post_start.clone();
+                {
+                    let mut logs = logs.write().await;
+                    let state = logs.service_mut(svc_mode);
+                    state.hooks = hooks;
+                    triggers::arm(&mut state.triggers, &info.triggers, &info.work_dir);
+                    state.fired.clear();
+                }
+                App::run_command(Arc::clone(&logs), config.limit, info, svc_mode, trigger);
+                if let Some(hook) = post_start {
+                    let (shared, limit) = (Arc::clone(&logs), config.limit);
+                    supervise(&logs, "post_start", async move {
+                        if App::wait_ready(&shared, svc_mode).await {
+                            App::run_hook(&shared, svc_mode, limit, "post_start", &hook).await;
+                        }
+                    });
+                }
+            }
+            if svc != command && !App::wait_ready(&logs, svc_mode).await {
+                logs.write().await.service_mut(mode).push(
+                    format!(
+                        "Dependency {:?} is not ready, {:?} not started",
+                        svc, command
+                    ),
+                    config.limit,
+                );
+                return;
+            }
+        }
+    }
+
+    /// stops `command`, waits until the old process has exited and starts it again,
+    /// its dependencies are started when needed
+    async fn restart_service(logs: Arc<RwLock<LogLists>>, config: Config, command: MenuCommand) {
+        let Some(mode) = CommandMode::of(command) else {
+            return;
+        };
+        let pid = {
+            let mut logs = logs.write().await;
+            let state = logs.service_mut(mode);
+            if state.restarting {
+                state.push(format!("{:?} is already restarting", command), config.limit);
+                return;
+            }
+            state.restarting = true;
+            state.pid
+        };
+        if let Some(pid) = pid {
+            App::stop_service(Arc::clone(&logs), config.limit, command).await;
+            // a forced kill frees the slot before the old process is reaped
+            if !App::wait_exit(&logs, mode, pid, STOP_TIMEOUT).await {
                 let mut logs = logs.write().await;
-                let line = match line {
-                    Ok(l) => App::trim_line(l),
-                    Err(_) => String::from("----deducted---"),
+                let state = logs.service_mut(mode);
+                state.restarting = false;
+                state.push(
+                    format!("Still running, not restarting :{}", pid),
+                    config.limit,
+                );
+                return;
+            }
+        }
+        logs.write()
+            .await
+            .service_mut(mode)
+            .push(format!("Restarting {:?}", command), config.limit);
+        App::start_service(Arc::clone(&logs), config, command, Trigger::Restart).await;
+        logs.write().await.service_mut(mode).restarting = false;
+    }
+
+    /// samples the process tree of every running service once a second and acts on its thresholds
+    async fn monitor(logs: Arc<RwLock<LogLists>>, config: watch::Receiver<Config>) {
+        let mut sampler = Sampler::default();
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            task::block_in_place(|| sampler.refresh());
+            // edited thresholds and services apply from the next tick
+            let config = config.borrow().clone();
+            for svc in config.service_order().unwrap_or_default() {
+                let Some(mode) = CommandMode::of(svc) else {
+                    continue;
+                };
+                let mut guard = logs.write().await;
+                let state = guard.service_mut(mode);
+                let Some((pid, sample)) = state
+                    .pid
+                    .and_then(|pid| sampler.sample(pid).map(|sample| (pid, sample)))
+                else {
+                    state.usage.stopped();
+                    continue;
                 };
+                state.usage.record(pid, sample);
+                let Some(thresholds) = config.find(svc).and_then(|c| c.thresholds.as_ref()) else {
+                    continue;
+                };
+                let Some(exceeded) = state.usage.check(thresholds) else {
+                    continue;
+                };
+                let line = format!("[warn] {:?} {}", svc, exceeded);
+                state.push(line.clone(), config.limit);
+                guard.debug_logs.push(line);
+                if thresholds.action == ThresholdAction::Restart {
+                    guard.debug_logs.push(format!("Restarting {:?}", svc));
+                    drop(guard);
+                    supervise(
+                        &logs,
+                        "restart_service",
+                        App::restart_service(Arc::clone(&logs), config.clone(), svc),
+                    );
+                }
+            }
+        }
+    }
 
-                match menucommand {
-                    MenuCommand::ComfyRun => {
-                        logs.comfyui_pid = cmd.id();
+    async fn wait_ready(logs: &Arc<RwLock<LogLists>>, mode: CommandMode) -> bool {
+        let deadline = tokio::time::Instant::now() + READY_TIMEOUT;
+        while tokio::time::Instant::now() < deadline {
+            let logs = logs.read().await;
+            let state = logs.service(mode);
+            if state.ready {
+                return true;
+            }
+            if !state.isrunning {
+                return false;
+            }
+            drop(logs);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        false
+    }
+
+    /// asks the service to exit, kills it when it is still alive after `STOP_TIMEOUT`,
+    /// its `pre_stop` and `post_stop` hooks run around that
+    async fn stop_service(logs: Arc<RwLock<LogLists>>, limit: usize, command: MenuCommand) {
+        let Some(mode) = CommandMode::of(command) else {
+            return;
+        };
+        let hooks = {
+            let mut logs = logs.write().await;
+            let state = logs.service_mut(mode);
+            if state.pid.is_none() {
+                return;
+            }
+            state.logs.clear();
+            state.hooks.clone()
+        };
+        if let Some(hook) = &hooks.pre_stop {
+            if !App::run_hook(&logs, mode, limit, "pre_stop", hook).await && hook.abort {
+                logs.write()
+                    .await
+                    .service_mut(mode)
+                    .push(format!("{:?} not stopped, pre_stop failed", command), limit);
+                return;
+            }
+        }
+        let (sender, paused) = {
+            let mut logs = logs.write().await;
+            let state = logs.service_mut(mode);
+            let Some(sender) = state.pid else {
+                return;
+            };
+            state.logs.push(format!("Sending Exit Signal :{}", sender));
+            state.stopping = true;
+            (sender, std::mem::take(&mut state.paused))
+        };
+        // a stopped process only sees the exit signal once it runs again, the tree is
+        // looked up without the logs held as that takes a while
+        if paused {
+            let _ = task::block_in_place(|| explorer::signal_tree(sender, Signal::Continue));
+        }
+        if let Err(err) = App::kill_command(sender, false) {
+            let mut logs = logs.write().await;
+            let state = logs.service_mut(mode);
+            state.isrunning = false;
+            state.stopping = false;
+            state.logs.push(format!("Failed to terminate pid :{}", err));
+            return;
+        }
+        if App::wait_exit(&logs, mode, sender, STOP_TIMEOUT).await {
+            logs.write()
+                .await
+                .service_mut(mode)
+                .logs
+                .push(format!("Success :{}", sender));
+        } else {
+            logs.write()
+                .await
+                .service_mut(mode)
+                .logs
+                .push(format!("Still running, killing :{}", sender));
+            let killed = App::kill_command(sender, true);
+            let exited = killed.is_ok() && App::wait_exit(&logs, mode, sender, KILL_TIMEOUT).await;
+            let mut logs = logs.write().await;
+            let state = logs.service_mut(mode);
+            if let Err(err) = killed {
+                state.logs.push(format!("Failed to terminate pid :{}", err));
+            }
+            if !exited {
+                // post_stop would run next to the process it is meant to clean up after
+                state.isrunning = false;
+                state.logs.push(format!(
+                    "[warn] {} is still running, post_stop skipped",
+                    sender
+                ));
+                return;
+            }
+        }
+        if let Some(hook) = &hooks.post_stop {
+            App::run_hook(&logs, mode, limit, "post_stop", hook).await;
+        }
+    }
+    /// runs one lifecycle hook of the service in `mode`, true when it exited with 0
+    async fn run_hook(
+        logs: &Arc<RwLock<LogLists>>,
+        mode: CommandMode,
+        limit: usize,
+        name: &str,
+        hook: &Hook,
+    ) -> bool {
+        let panel = mode.panel();
+        let push = |logs: &mut LogLists, line: String| logs.push_panel(panel, line, limit);
+        push(
+            &mut *logs.write().await,
+            format!("Hook {} :{} {}", name, hook.exe_path, hook.args.join(" ")),
+        );
+        let mut command = Command::new(&hook.exe_path);
+        command.args(&hook.args).envs(&hook.env);
+        if !hook.work_dir.is_empty() {
+            command.current_dir(&hook.work_dir);
+        }
+        let outcome = App::run_program(logs, command, push, |pid| {
+            Watchdog::start(logs, panel, limit, pid, hook.timeout, None)
+        })
+        .await;
+        if !outcome.succeeded() {
+            let line = format!("Hook {} failed :{}", name, outcome.label());
+            push(&mut *logs.write().await, line);
+        }
+        outcome.succeeded()
+    }
+    /// runs `hook` in the background, for hooks nothing waits on
+    fn spawn_hook(
+        logs: &Arc<RwLock<LogLists>>,
+        mode: CommandMode,
+        limit: usize,
+        name: &'static str,
+        hook: Hook,
+    ) {
+        let shared = Arc::clone(logs);
+        supervise(logs, name, async move {
+            App::run_hook(&shared, mode, limit, name, &hook).await;
+        });
+    }
+
+    /// freezes the service and everything it spawned, or lets them continue
+    async fn toggle_pause(logs: Arc<RwLock<LogLists>>, limit: usize, command: MenuCommand) {
+        let Some(mode) = CommandMode::of(command) else {
+            return;
+        };
+        let (pid, paused) = {
+            let logs = logs.read().await;
+            let state = logs.service(mode);
+            (state.pid, state.paused)
+        };
+        let Some(pid) = pid else {
+            logs.write()
+                .await
+                .service_mut(mode)
+                .push(format!("{:?} is not running", command), limit);
+            return;
+        };
+        let signal = if paused {
+            Signal::Continue
+        } else {
+            Signal::Stop
+        };
+        let result = task::block_in_place(|| explorer::signal_tree(pid, signal));
+        let mut logs = logs.write().await;
+        let state = logs.service_mut(mode);
+        let line = match result {
+            Ok(count) if state.pid == Some(pid) => {
+                state.paused = !paused;
+                let action = if paused { "Resumed" } else { "Paused" };
+                format!("{} :{} ({} processes)", action, pid, count)
+            }
+            Ok(_) => format!("Exited before {:?} arrived :{}", signal, pid),
+            Err(err) => format!("Failed to send {:?} :{}", signal, err),
+        };
+        state.push(line, limit);
+    }
+
+    async fn wait_exit(
+        logs: &Arc<RwLock<LogLists>>,
+        mode: CommandMode,
+        pid: u32,
+        timeout: Duration,
+    ) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while tokio::time::Instant::now() < deadline {
+            if logs.read().await.service(mode).pid != Some(pid) {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        false
+    }
+
+    #[cfg(windows)]
+    fn kill_command(pid: u32, force: bool) -> Result<()> {
+        let pid = pid.to_string();
+        let mut args = vec!["/PID", pid.as_str()];
+        if force {
+            args.insert(0, "/F");
+        }
+        let _ = Command::new("taskkill")
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        Ok(())
+    }
+    #[cfg(not(windows))]
+    fn kill_command(pid: u32, force: bool) -> Result<()> {
+        let signal = if force { "-KILL" } else { "-TERM" };
+        let _ = Command::new("kill")
+            .args([signal, pid.to_string().as_str()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        Ok(())
+    }
+    #[cfg(windows)]
+    fn shell_command(line: &str) -> Command {
+        let mut cmd = Command::new("pwsh");
+        cmd.args(["-NoProfile", "-Command", line]);
+        cmd
+    }
+    #[cfg(not(windows))]
+    fn shell_command(line: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", line]);
+        cmd
+    }
+    fn run_quick_command(&mut self, action: QuickAction) {
+        let logs = Arc::clone(&self.logs);
+        let limit = self.config.limit;
+
+        supervise(&self.logs, "run_quick_command", async move {
+            let panel = action.panel;
+            let push = |logs: &mut LogLists, line: String| logs.push_panel(panel, line, limit);
+            let Some((job, _slot)) = App::queue_job(
+                &logs,
+                panel,
+                limit,
+                &format!("quick/{}", action.key),
+                &action.name,
+            )
+            .await
+            else {
+                return;
+            };
+            let cmd = if action.shell {
+                let mut line = vec![action.command.clone()];
+                line.extend(action.args.iter().cloned());
+                App::shell_command(&line.join(" "))
+            } else {
+                let mut cmd = Command::new(&action.command);
+                cmd.args(&action.args);
+                cmd
+            };
+            push(
+                &mut *logs.write().await,
+                format!("{} {}", action.command, action.args.join(" ")),
+            );
+            let outcome = App::run_program(&logs, cmd, push, |pid| {
+                Watchdog::start(&logs, panel, limit, pid, None, Some(&job))
+            })
+            .await;
+            let line = match &outcome {
+                Outcome::Failed(err) => format!("[error] {}", err),
+                outcome => format!("Finished :{}", outcome.label()),
+            };
+            let mut logs = logs.write().await;
+            push(&mut logs, line);
+            if App::alarming(&outcome) {
+                let line = format!("{} failed :{}", action.name, outcome.label());
+                logs.alerts.raise(Severity::Error, line);
+            }
+            logs.jobs.finish(job.id, outcome);
+        });
+    }
+
+    async fn process_menu(&mut self, index: usize, terminal: &mut DefaultTerminal) -> Result<()> {
+        let menuinfo = self.menu_list.items[index].clone();
+        let logs = Arc::clone(&self.logs);
+        let config = self.config.clone();
+        let menucommand = menuinfo.cmd;
+        if let Some(cmdinfo) = self
+            .selected_config
+            .clone()
+            .filter(|c| !c.params.is_empty())
+        {
+            self.prompt = Some(ParamPrompt::new(cmdinfo, index, &self.recent));
+            return Ok(());
+        }
+        if let Some(cmdinfo) = self.selected_config.clone().filter(|c| !c.steps.is_empty()) {
+            self.start_pipeline(cmdinfo).await;
+            return Ok(());
+        }
+        match menucommand {
+            MenuCommand::ComfyRun | MenuCommand::CronRun => {
+                // the filled in values replace the entry for this start only
+                let mut config = config;
+                if let Some(cmdinfo) = self.selected_config.clone() {
+                    if let Some(entry) = config.commands.get_mut(index) {
+                        *entry = cmdinfo;
                     }
-                    MenuCommand::CronRun => {
-                        logs.cron_pid = cmd.id();
+                }
+                let task =
+                    App::start_service(Arc::clone(&logs), config, menucommand, Trigger::Manual);
+                supervise(&logs, "start_service", task);
+            }
+            MenuCommand::ComfyKill | MenuCommand::CronKill => {
+                if let Some(service) = menucommand.service_of() {
+                    self.stop_with_dependents(service).await;
+                }
+            }
+            MenuCommand::ComfyRestart | MenuCommand::CronRestart => {
+                if let Some(service) = menucommand.service_of() {
+                    let task = App::restart_service(logs, config, service);
+                    supervise(&self.logs, "restart_service", task);
+                }
+            }
+            MenuCommand::ComfyPause | MenuCommand::CronPause => {
+                if let Some(service) = menucommand.service_of() {
+                    let task = App::toggle_pause(logs, config.limit, service);
+                    supervise(&self.logs, "toggle_pause", task);
+                }
+            }
+            MenuCommand::ComfySignal | MenuCommand::CronSignal => {
+                let target = menucommand.service_of().zip(CommandMode::of(menucommand));
+                if let Some((service, mode)) = target {
+                    let pid = logs.read().await.service(mode).pid;
+                    match pid {
+                        Some(pid) => {
+                            self.dialog = Some(App::signal_dialog(pid, &format!("{:?}", service)));
+                        }
+                        None => logs
+                            .write()
+                            .await
+                            .service_mut(mode)
+                            .push(format!("{:?} is not running", service), config.limit),
                     }
-                    _ => {}
                 }
-                match mode {
-                    CommandMode::COMFYUI => {
-                        logs.comfyui_logs.push(line);
-                        if logs.comfyui_logs.len() > self.config.limit {
-                            logs.comfyui_logs.remove(0);
+            }
+            MenuCommand::ComfyUpdate | MenuCommand::Custom => {
+                let cmdinfo = self.selected_config.clone();
+                if let Some((cmdinfo, mode)) =
+                    cmdinfo.and_then(|c| CommandMode::of_entry(&c).map(|mode| (c, mode)))
+                {
+                    App::run_command(logs, config.limit, cmdinfo, mode, Trigger::Manual);
+                }
+            }
+            MenuCommand::ComfyNodes => self.open_nodes(),
+            MenuCommand::Packages => {
+                self.packages.reload();
+                self.view = View::Packages;
+            }
+            MenuCommand::StartAll => {
+                supervise(&self.logs, "start_all", async move {
+                    for svc in config.service_order().unwrap_or_default() {
+                        App::start_service(Arc::clone(&logs), config.clone(), svc, Trigger::Manual)
+                            .await;
+                    }
+                });
+            }
+            MenuCommand::StopAll => {
+                supervise(&self.logs, "stop_all", async move {
+                    for svc in config.service_order().unwrap_or_default().into_iter().rev() {
+                        App::stop_service(Arc::clone(&logs), config.limit, svc).await;
+                    }
+                });
+            }
+            MenuCommand::Config => {
+                let _ = self.run_editor(terminal).await;
+
+                //self.debug = self.config.to_string();
+            }
+            MenuCommand::About => {
+                //let _ = self.run_editor(terminal).await;
+
+                //self.debug = self.config.to_string();
+            }
+            MenuCommand::Exit => {
+                self.request_exit(self.config.on_exit).await;
+            }
+        }
+        Ok(())
+    }
+    /// runs the steps of `cmdinfo`, or offers to cancel the run that is still going
+    async fn start_pipeline(&mut self, cmdinfo: CmdInfo) {
+        let running = self
+            .logs
+            .read()
+            .await
+            .pipelines
+            .get(&cmdinfo.key())
+            .is_some_and(|p| !p.finished);
+        if running {
+            self.dialog = Some(
+                Dialog::new(
+                    "Pipeline",
+                    vec![format!("{} is still running", cmdinfo.name)],
+                )
+                .option(
+                    'y',
+                    "cancel it",
+                    DialogAction::CancelPipeline(cmdinfo.key()),
+                ),
+            );
+            return;
+        }
+        let task = App::run_pipeline(Arc::clone(&self.logs), self.config.clone(), cmdinfo);
+        supervise(&self.logs, "run_pipeline", task);
+    }
+    /// runs the steps one after another as a job, a failed step ends the run unless it
+    /// may continue on error
+    async fn run_pipeline(logs: Arc<RwLock<LogLists>>, config: Config, cmdinfo: CmdInfo) {
+        let key = cmdinfo.key();
+        let panel = CommandMode::of_entry(&cmdinfo).map_or(LogPanel::Debug, |mode| mode.panel());
+        let limit = config.limit;
+        let total = cmdinfo.steps.len();
+        let Some((job, _slot)) =
+            App::queue_job(&logs, panel, limit, &key, cmdinfo.name.trim()).await
+        else {
+            return;
+        };
+        let (pipeline, mut cancelled) = Pipeline::new(&cmdinfo.steps);
+        let run = {
+            let mut logs = logs.write().await;
+            if logs.pipelines.get(&key).is_some_and(|p| !p.finished) {
+                logs.jobs.finish(job.id, Outcome::Cancelled);
+                return;
+            }
+            logs.pipelines.insert(key.clone(), pipeline);
+            logs.push_panel(
+                panel,
+                format!("Pipeline {} :{} steps", cmdinfo.name, total),
+                limit,
+            );
+            logs.begin_run(&cmdinfo, Trigger::Manual)
+        };
+        let before = App::take_snapshots(&logs, &config, panel, &cmdinfo.name, "before").await;
+        let mut outcome = Outcome::Finished;
+        let mut updated = false;
+        for (i, step) in cmdinfo.steps.iter().enumerate() {
+            {
+                let mut logs = logs.write().await;
+                if let Some(pipeline) = logs.pipelines.get_mut(&key) {
+                    pipeline.set(i, StepStatus::Running);
+                }
+                logs.push_panel(
+                    panel,
+                    format!("Step {}/{} :{}", i + 1, total, step.label()),
+                    limit,
+                );
+            }
+            // a git step runs to its end, dropped between stash and pop it would leave
+            // local changes stashed, a cancel stops the pipeline at the next step
+            let interruptible = !matches!(step.kind, StepKind::Git { .. });
+            let result = tokio::select! {
+                biased;
+                Ok(_) = cancelled.wait_for(|cancelled| *cancelled), if interruptible => None,
+                _ = job.cancelled(), if interruptible => None,
+                result = App::run_step(&logs, &config, &cmdinfo, step, panel) => Some(result),
+            };
+            let status = match result {
+                Some(Ok(())) => StepStatus::Done,
+                Some(Err(err)) => StepStatus::Failed(err),
+                None => StepStatus::Cancelled,
+            };
+            let stop = match &status {
+                StepStatus::Failed(err) if !step.continue_on_error => {
+                    outcome = Outcome::Failed(format!("{} :{}", step.label(), err));
+                    true
+                }
+                StepStatus::Cancelled => {
+                    outcome = Outcome::Cancelled;
+                    true
+                }
+                _ => false,
+            };
+            let mut logs = logs.write().await;
+            if let StepStatus::Failed(err) = &status {
+                logs.push_panel(panel, format!("Step failed :{}", err), limit);
+            }
+            match (&step.kind, &status) {
+                (StepKind::Git { .. }, StepStatus::Done) => {
+                    updated = logs
+                        .rollback
+                        .as_ref()
+                        .is_some_and(|r| r.previous != r.current);
+                }
+                (StepKind::Service { service, action }, StepStatus::Failed(_))
+                    if updated && *action != ServiceAction::Stop =>
+                {
+                    if let Some(rollback) = logs.rollback.as_mut() {
+                        rollback.failed = Some(*service);
+                    }
+                }
+                _ => {}
+            }
+            if let Some(pipeline) = logs.pipelines.get_mut(&key) {
+                pipeline.set(i, status);
+            }
+            if stop {
+                break;
+            }
+        }
+        App::compare_snapshots(&logs, &config, panel, &cmdinfo.name, before).await;
+        let shared = Arc::clone(&logs);
+        let mut logs = logs.write().await;
+        let mut steps = Vec::new();
+        if let Some(pipeline) = logs.pipelines.get_mut(&key) {
+            pipeline.finish();
+            steps = pipeline.lines();
+        }
+        let (result, severity) = match &outcome {
+            Outcome::Failed(_) => ("failed", Severity::Error),
+            Outcome::Cancelled => ("cancelled", Severity::Warn),
+            _ => ("finished", Severity::Info),
+        };
+        let line = format!("Pipeline {} {}", cmdinfo.name, result);
+        logs.push_panel(panel, line.clone(), limit);
+        logs.alerts.raise(severity, line);
+        logs.jobs.finish(job.id, outcome.clone());
+        let run = logs.end_run(run, outcome, steps);
+        drop(logs);
+        App::save_run(&shared, run).await;
+    }
+    /// freezes the python of every service with `pip_snapshots`
+    async fn take_snapshots(
+        logs: &Arc<RwLock<LogLists>>,
+        config: &Config,
+        panel: LogPanel,
+        what: &str,
+        when: &str,
+    ) -> Vec<Snapshot> {
+        let mut taken = Vec::new();
+        for cmd in config.commands.iter().filter(|c| c.pip_snapshots) {
+            let label = format!("{} {}", when, what.trim());
+            let line = match snapshot::take(cmd.command, &cmd.exe_path, &label).await {
+                Ok(snapshot) => {
+                    let line = format!(
+                        "Packages of {:?} :{} ({})",
+                        cmd.command,
+                        snapshot.packages.len(),
+                        label
+                    );
+                    taken.push(snapshot);
+                    line
+                }
+                Err(err) => format!("[error] Snapshot of {:?} failed :{}", cmd.command, err),
+            };
+            logs.write().await.push_panel(panel, line, config.limit);
+        }
+        taken
+    }
+    /// freezes again and reports what changed since `before`
+    async fn compare_snapshots(
+        logs: &Arc<RwLock<LogLists>>,
+        config: &Config,
+        panel: LogPanel,
+        what: &str,
+        before: Vec<Snapshot>,
+    ) {
+        if before.is_empty() {
+            return;
+        }
+        let after = App::take_snapshots(logs, config, panel, what, "after").await;
+        let mut logs = logs.write().await;
+        for old in before {
+            let Some(new) = after.iter().find(|s| s.service == old.service) else {
+                continue;
+            };
+            let changes = snapshot::diff(&old.packages, &new.packages);
+            let lines = [format!(
+                "Packages of {:?} :{}",
+                old.service,
+                snapshot::summary(&changes)
+            )]
+            .into_iter()
+            .chain(changes.iter().map(|c| format!("  {}", c.line())));
+            for line in lines {
+                logs.push_panel(panel, line, config.limit);
+            }
+        }
+    }
+    /// puts the python of the snapshot's service back to the frozen packages,
+    /// with the service stopped and a snapshot of the current state taken first
+    async fn restore_packages(logs: Arc<RwLock<LogLists>>, config: Config, target: Snapshot) {
+        let service = target.service;
+        let Some(mode) = CommandMode::of(service) else {
+            return;
+        };
+        let Some(python) = config.find(service).map(|c| c.exe_path.clone()) else {
+            return;
+        };
+        let panel = mode.panel();
+        let limit = config.limit;
+        let pid = logs.read().await.service(mode).pid;
+        if let Some(pid) = pid {
+            App::stop_service(Arc::clone(&logs), limit, service).await;
+            if !App::wait_exit(&logs, mode, pid, STOP_TIMEOUT).await {
+                logs.write().await.push_panel(
+                    panel,
+                    format!("[error] {:?} is still running, not restoring", service),
+                    limit,
+                );
+                return;
+            }
+        }
+        let label = format!("restore to {}", format_time(target.taken / 1000));
+        let current = match snapshot::take(service, &python, &format!("before {}", label)).await {
+            Ok(current) => current,
+            Err(err) => {
+                logs.write().await.push_panel(
+                    panel,
+                    format!("[error] Restore failed :{}", err),
+                    limit,
+                );
+                return;
+            }
+        };
+        let extra = current
+            .packages
+            .keys()
+            .filter(|name| !target.packages.contains_key(*name))
+            .cloned()
+            .collect::<Vec<_>>();
+        let push = |logs: &mut LogLists, line: String| logs.push_panel(panel, line, limit);
+        let mut result = Ok(());
+        if !extra.is_empty() {
+            let mut command = Command::new(&python);
+            command.args(["-m", "pip", "uninstall", "-y"]).args(&extra);
+            result = App::run_to_end(&logs, command, push).await;
+        }
+        if result.is_ok() {
+            let mut command = Command::new(&python);
+            command
+                .args(["-m", "pip", "install", "-r"])
+                .arg(&target.path);
+            result = App::run_to_end(&logs, command, push).await;
+        }
+        let line = match &result {
+            Ok(()) => format!(
+                "Packages of {:?} restored to {} UTC",
+                service,
+                format_time(target.taken / 1000)
+            ),
+            Err(err) => format!("[error] Restore failed :{}", err),
+        };
+        logs.write().await.push_panel(panel, line, limit);
+        if result.is_ok() && pid.is_some() {
+            App::start_service(logs, config, service, Trigger::Restart).await;
+        }
+    }
+    async fn run_step(
+        logs: &Arc<RwLock<LogLists>>,
+        config: &Config,
+        cmdinfo: &CmdInfo,
+        step: &Step,
+        panel: LogPanel,
+    ) -> std::result::Result<(), String> {
+        match &step.kind {
+            StepKind::Service { service, action } => {
+                let mode = CommandMode::of(*service)
+                    .ok_or_else(|| format!("{:?} is not a service", service))?;
+                let pid = logs.read().await.service(mode).pid;
+                match action {
+                    ServiceAction::Stop => {
+                        App::stop_service(Arc::clone(logs), config.limit, *service).await;
+                        match pid {
+                            Some(pid) if !App::wait_exit(logs, mode, pid, STOP_TIMEOUT).await => {
+                                Err(format!("{:?} is still running", service))
+                            }
+                            _ => Ok(()),
                         }
                     }
-                    CommandMode::CRON => {
-                        logs.cron_logs.push(line);
-                        if logs.cron_logs.len() > self.config.limit {
-                            logs.cron_logs.remove(0);
+                    ServiceAction::Start | ServiceAction::Restart => {
+                        let shared = Arc::clone(logs);
+                        if *action == ServiceAction::Start {
+                            App::start_service(shared, config.clone(), *service, Trigger::Pipeline)
+                                .await;
+                        } else {
+                            App::restart_service(shared, config.clone(), *service).await;
+                        }
+                        if App::wait_ready(logs, mode).await {
+                            Ok(())
+                        } else {
+                            Err(format!("{:?} did not get ready", service))
                         }
                     }
                 }
             }
+            StepKind::Git {
+                git: GitAction::Update,
+                dirty,
+                work_dir,
+            } => {
+                let dir = if work_dir.is_empty() {
+                    &cmdinfo.work_dir
+                } else {
+                    work_dir
+                };
+                // a deadline instead of a timeout around the update, dropping it
+                // halfway would leave local changes in the stash
+                let deadline = cmdinfo
+                    .timeout
+                    .map(|secs| Instant::now() + Duration::from_secs(secs));
+                let update = git::update(dir, *dirty, deadline).await?;
+                let mut logs = logs.write().await;
+                if update.stashed {
+                    let line = match &update.stash_error {
+                        None => "Stashed local changes and put them back".to_string(),
+                        Some(err) => {
+                            format!("[warn] Local changes are still in git stash :{}", err)
+                        }
+                    };
+                    logs.push_panel(panel, line, config.limit);
+                }
+                if update.commits.is_empty() {
+                    // nothing to roll back, an older update's offer no longer fits
+                    logs.rollback = None;
+                    logs.push_panel(panel, "Already up to date".to_string(), config.limit);
+                    return Ok(());
+                }
+                let lines = [format!("Incoming commits :{}", update.commits.len())]
+                    .into_iter()
+                    .chain(update.commits.iter().map(|c| format!("  {}", c)))
+                    .chain(["Changed files".to_string()])
+                    .chain(update.files.iter().map(|f| format!("  {}", f)))
+                    .chain([format!(
+                        "Updated {} -> {}",
+                        git::short(&update.previous),
+                        git::short(&update.current)
+                    )]);
+                for line in lines {
+                    logs.push_panel(panel, line, config.limit);
+                }
+                logs.rollback = Some(Rollback {
+                    work_dir: dir.to_string(),
+                    previous: update.previous,
+                    current: update.current,
+                    panel,
+                    failed: None,
+                    offered: false,
+                });
+                Ok(())
+            }
+            StepKind::Run {
+                exe_path,
+                args,
+                work_dir,
+            } => {
+                let work_dir = if work_dir.is_empty() {
+                    &cmdinfo.work_dir
+                } else {
+                    work_dir
+                };
+                let push =
+                    |logs: &mut LogLists, line: String| logs.push_panel(panel, line, config.limit);
+                let outcome = App::retrying(
+                    logs,
+                    &step.label(),
+                    cmdinfo.retry.as_ref(),
+                    None,
+                    push,
+                    |_| {
+                        let mut command = Command::new(exe_path);
+                        command.args(args);
+                        if !work_dir.is_empty() {
+                            command.current_dir(work_dir);
+                        }
+                        App::run_program(logs, command, push, |pid| {
+                            Watchdog::start(logs, panel, config.limit, pid, cmdinfo.timeout, None)
+                        })
+                    },
+                )
+                .await;
+                match outcome {
+                    Outcome::Exit(0) => Ok(()),
+                    Outcome::Failed(err) => Err(err),
+                    _ => Err(outcome.label()),
+                }
+            }
+        }
+    }
+    /// runs `command` until it exits, every line it prints goes through `push`
+    async fn run_to_end(
+        logs: &Arc<RwLock<LogLists>>,
+        command: Command,
+        push: impl Fn(&mut LogLists, String),
+    ) -> std::result::Result<(), String> {
+        match App::run_program(logs, command, push, |_| None).await {
+            Outcome::Exit(0) => Ok(()),
+            Outcome::Failed(err) => Err(err),
+            outcome => Err(outcome.label()),
+        }
+    }
+    /// `run_to_end` with a `Watchdog` made by `watch` from the pid of the child
+    async fn run_program(
+        logs: &Arc<RwLock<LogLists>>,
+        mut command: Command,
+        push: impl Fn(&mut LogLists, String),
+        watch: impl FnOnce(Option<u32>) -> Option<Watchdog>,
+    ) -> Outcome {
+        command
+            .env("PYTHONUNBUFFERED", "1")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // a cancelled pipeline drops the step and with it the child
+            .kill_on_drop(true);
+        let program = command.as_std().get_program().to_string_lossy().to_string();
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(err) => return Outcome::Failed(format!("failed to execute {} :{}", program, err)),
+        };
+        let watchdog = watch(child.id());
+        let stdout = child.stdout.take().unwrap();
+        let stderr = child.stderr.take().unwrap();
+        let stdout = LinesStream::new(BufReader::new(stdout).lines());
+        let stderr = LinesStream::new(BufReader::new(stderr).lines());
+        let mut merged = StreamExt::merge(stdout, stderr);
+        while let Some(line) = merged.next().await {
+            let line = match line {
+                Ok(l) => App::trim_line(l),
+                Err(_) => String::from("----deducted---"),
+            };
+            push(&mut *logs.write().await, line);
+        }
+        let status = child.wait().await;
+        let stopped = watchdog.and_then(Watchdog::stop);
+        match (status, stopped) {
+            (Err(err), _) => Outcome::Failed(err.to_string()),
+            (Ok(_), Some(outcome)) => outcome,
+            (Ok(status), None) => status.code().map_or(Outcome::Killed, Outcome::Exit),
+        }
+    }
+    /// shows the custom nodes panel with freshly read repositories
+    fn open_nodes(&mut self) {
+        self.view = View::Nodes;
+        let task = App::scan_nodes(Arc::clone(&self.logs), self.config.clone(), false);
+        supervise(&self.logs, "scan_nodes", task);
+    }
+    /// lists the checkouts below `custom_nodes.dir` and reads where each one stands,
+    /// fetching their upstreams first when asked to
+    async fn scan_nodes(logs: Arc<RwLock<LogLists>>, config: Config, fetch: bool) {
+        let Some(nodes) = config.custom_nodes.clone() else {
+            logs.write()
+                .await
+                .debug_logs
+                .push("[warn] custom_nodes is not configured".to_string());
+            return;
+        };
+        let found = match nodes::scan(&nodes.dir) {
+            Ok(found) => found,
+            Err(err) => {
+                logs.write()
+                    .await
+                    .debug_logs
+                    .push(format!("[error] custom nodes {} :{}", nodes.dir, err));
+                return;
+            }
+        };
+        let paths = {
+            let mut logs = logs.write().await;
+            let mut known = std::mem::take(&mut logs.nodes);
+            logs.nodes = found
+                .into_iter()
+                .map(
+                    |(name, path)| match known.iter().position(|r| r.path == path) {
+                        Some(i) => known.swap_remove(i),
+                        None => NodeRepo {
+                            name,
+                            path,
+                            info: None,
+                            state: RepoState::Idle,
+                            logs: Vec::new(),
+                        },
+                    },
+                )
+                .collect();
+            // busy repositories are read again once they are done
+            let mut paths = Vec::new();
+            for repo in logs.nodes.iter_mut().filter(|r| !r.state.busy()) {
+                if fetch {
+                    repo.state = RepoState::Fetching;
+                }
+                paths.push(repo.path.clone());
+            }
+            paths
+        };
+        let limiter = Arc::new(Semaphore::new(nodes.parallel.max(1)));
+        let tasks = paths
+            .into_iter()
+            .map(|path| {
+                let shared = Arc::clone(&logs);
+                let limiter = Arc::clone(&limiter);
+                let limit = config.limit;
+                supervise(&logs, "scan_node", async move {
+                    let Ok(_permit) = limiter.acquire().await else {
+                        return;
+                    };
+                    let fetched = if fetch {
+                        git::fetch(&path).await
+                    } else {
+                        Ok(())
+                    };
+                    let info = git::info(&path).await;
+                    let mut logs = shared.write().await;
+                    let Some(repo) = logs.node_mut(&path) else {
+                        return;
+                    };
+                    match (info, fetched) {
+                        (Ok(info), Ok(())) => {
+                            repo.info = Some(info);
+                            if fetch {
+                                repo.state = RepoState::Idle;
+                            }
+                        }
+                        (info, fetched) => {
+                            let err = fetched.err().or(info.err()).unwrap_or_default();
+                            repo.push(err.clone(), limit);
+                            repo.state = RepoState::Failed(err);
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            let _ = task.await;
+        }
+    }
+    /// updates the repositories at `paths`, `custom_nodes.parallel` at a time
+    async fn update_nodes(logs: Arc<RwLock<LogLists>>, config: Config, paths: Vec<String>) {
+        let Some(nodes) = config.custom_nodes.clone() else {
+            return;
+        };
+        let python = match nodes.python.is_empty() {
+            true => config
+                .find(MenuCommand::ComfyRun)
+                .map(|c| c.exe_path.clone())
+                .unwrap_or_default(),
+            false => nodes.python.clone(),
+        };
+        let limit = config.limit;
+        let paths = {
+            let mut logs = logs.write().await;
+            let mut queued = Vec::new();
+            for path in paths {
+                let Some(repo) = logs.node_mut(&path).filter(|r| !r.state.busy()) else {
+                    continue;
+                };
+                repo.state = RepoState::Updating;
+                repo.push("Update".to_string(), limit);
+                queued.push(path);
+            }
+            queued
+        };
+        if paths.is_empty() {
+            return;
+        }
+        let what = "custom nodes update";
+        let panel = LogPanel::Debug;
+        let before = App::take_snapshots(&logs, &config, panel, what, "before").await;
+        let limiter = Arc::new(Semaphore::new(nodes.parallel.max(1)));
+        // pip installs into one environment, two at once can break it
+        let pip = Arc::new(Semaphore::new(1));
+        let tasks = paths
+            .iter()
+            .map(|path| {
+                let shared = Arc::clone(&logs);
+                let (limiter, pip) = (Arc::clone(&limiter), Arc::clone(&pip));
+                let path = path.clone();
+                let python = python.clone();
+                supervise(&logs, "update_node", async move {
+                    let state = match limiter.acquire().await {
+                        Ok(slot) => {
+                            App::update_node(&shared, limit, &path, &python, slot, &pip).await
+                        }
+                        Err(err) => RepoState::Failed(err.to_string()),
+                    };
+                    let info = git::info(&path).await;
+                    let mut logs = shared.write().await;
+                    let Some(repo) = logs.node_mut(&path) else {
+                        return;
+                    };
+                    if let RepoState::Failed(err) = &state {
+                        repo.push(format!("Failed :{}", err), limit);
+                    }
+                    repo.state = state;
+                    repo.info = info.ok().or(repo.info.take());
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            let _ = task.await;
+        }
+        App::compare_snapshots(&logs, &config, panel, what, before).await;
+        let mut logs = logs.write().await;
+        let count = |state: &RepoState| {
+            logs.nodes
+                .iter()
+                .filter(|r| paths.contains(&r.path) && r.state == *state)
+                .count()
+        };
+        let updated = count(&RepoState::Updated);
+        let current = count(&RepoState::UpToDate);
+        let failed = paths.len() - updated - current;
+        let summary = format!(
+            "Custom nodes :{} updated, {} up to date, {} failed",
+            updated, current, failed
+        );
+        let severity = if failed > 0 {
+            Severity::Warn
+        } else {
+            Severity::Info
+        };
+        logs.alerts.raise(severity, summary.clone());
+        logs.debug_logs.push(if failed > 0 {
+            format!("[warn] {}", summary)
+        } else {
+            summary
         });
     }
-
-    fn kill_command(pid: u32) -> Result<()> {
-        let _ = Command::new("taskkill")
-            .args(vec!["/F", "/PID", format!("{}", pid).as_str()])
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()?;
-        Ok(())
+    /// fast-forwards one repository and installs its requirements when they changed, `slot`
+    /// is given back for the next git update before waiting for its turn at `pip`
+    async fn update_node(
+        logs: &Arc<RwLock<LogLists>>,
+        limit: usize,
+        path: &str,
+        python: &str,
+        slot: SemaphorePermit<'_>,
+        pip: &Semaphore,
+    ) -> RepoState {
+        let push = |logs: &mut LogLists, line: String| {
+            if let Some(repo) = logs.node_mut(path) {
+                repo.push(line, limit);
+            }
+        };
+        let update = match git::update(path, DirtyPolicy::Refuse, None).await {
+            Ok(update) => update,
+            Err(err) => return RepoState::Failed(err),
+        };
+        if update.previous == update.current {
+            push(&mut *logs.write().await, "Already up to date".to_string());
+            return RepoState::UpToDate;
+        }
+        {
+            let mut logs = logs.write().await;
+            for commit in &update.commits {
+                push(&mut logs, format!("  {}", commit));
+            }
+            push(
+                &mut logs,
+                format!(
+                    "Updated {} -> {}",
+                    git::short(&update.previous),
+                    git::short(&update.current)
+                ),
+            );
+        }
+        let requirements = "requirements.txt";
+        if !nodes::has_requirements(path) {
+            return RepoState::Updated;
+        }
+        match git::changed(path, &update.previous, &update.current, requirements).await {
+            Ok(true) => {}
+            Ok(false) => return RepoState::Updated,
+            Err(err) => return RepoState::Failed(err),
+        }
+        if python.is_empty() {
+            return RepoState::Failed("requirements changed but no python is configured".into());
+        }
+        drop(slot);
+        if pip.available_permits() == 0 {
+            push(
+                &mut *logs.write().await,
+                "Waiting for another pip install".to_string(),
+            );
+        }
+        let _pip = match pip.acquire().await {
+            Ok(permit) => permit,
+            Err(err) => return RepoState::Failed(err.to_string()),
+        };
+        {
+            let mut logs = logs.write().await;
+            push(&mut logs, "Installing requirements".to_string());
+            if let Some(repo) = logs.node_mut(path) {
+                repo.state = RepoState::Installing;
+            }
+        }
+        let mut command = Command::new(python);
+        command
+            .args(["-m", "pip", "install", "-r", requirements])
+            .current_dir(path);
+        match App::run_to_end(logs, command, push).await {
+            Ok(()) => RepoState::Updated,
+            Err(err) => RepoState::Failed(format!("requirements :{}", err)),
+        }
+    }
+    /// carries out what output triggers set off since the last tick
+    async fn run_triggers(&mut self) {
+        let limit = self.config.limit;
+        for mode in [CommandMode::Comfyui, CommandMode::Cron] {
+            let fired = std::mem::take(&mut self.logs.write().await.service_mut(mode).fired);
+            for action in fired {
+                match action {
+                    TriggerAction::Bell => {
+                        let mut out = stdout();
+                        let _ = out.write_all(b"\x07").and_then(|_| out.flush());
+                    }
+                    TriggerAction::Toast(text) => {
+                        self.logs.write().await.alerts.raise(Severity::Info, text)
+                    }
+                    TriggerAction::Restart => {
+                        let command = mode.service();
+                        self.logs
+                            .write()
+                            .await
+                            .service_mut(mode)
+                            .push(format!("Trigger restarts {:?}", command), limit);
+                        supervise(
+                            &self.logs,
+                            "restart_service",
+                            App::restart_service(
+                                Arc::clone(&self.logs),
+                                self.config.clone(),
+                                command,
+                            ),
+                        );
+                    }
+                    TriggerAction::Run(hook) => {
+                        App::spawn_hook(&self.logs, mode, limit, "trigger", hook)
+                    }
+                    TriggerAction::Ready | TriggerAction::Flash => {}
+                }
+            }
+        }
+    }
+    /// asks once whether to go back when a service failed to start after an update
+    async fn offer_rollback(&mut self) {
+        if self.dialog.is_some() {
+            return;
+        }
+        let pending = self
+            .logs
+            .read()
+            .await
+            .rollback
+            .as_ref()
+            .is_some_and(|r| r.failed.is_some() && !r.offered);
+        if !pending {
+            return;
+        }
+        let mut logs = self.logs.write().await;
+        let Some(rollback) = logs.rollback.as_mut() else {
+            return;
+        };
+        let Some(failed) = rollback.failed else {
+            return;
+        };
+        rollback.offered = true;
+        let previous = git::short(&rollback.previous);
+        self.dialog = Some(
+            Dialog::new(
+                "Update failed",
+                vec![
+                    format!("{:?} did not start after the update", failed),
+                    format!("{} -> {}", previous, git::short(&rollback.current)),
+                ],
+            )
+            .option(
+                'b',
+                &format!("roll back to {}", previous),
+                DialogAction::Rollback,
+            ),
+        );
+    }
+    /// resets the work tree to the commit before the last update and restarts the failed service
+    async fn rollback(logs: Arc<RwLock<LogLists>>, config: Config) {
+        let Some(rollback) = logs.write().await.rollback.take() else {
+            return;
+        };
+        if let Some(service) = rollback.failed {
+            App::stop_service(Arc::clone(&logs), config.limit, service).await;
+        }
+        let result = git::reset(&rollback.work_dir, &rollback.previous).await;
+        {
+            let mut logs = logs.write().await;
+            let line = match &result {
+                Ok(()) => format!("Rolled back to {}", git::short(&rollback.previous)),
+                Err(err) => format!("[error] Rollback failed :{}", err),
+            };
+            logs.push_panel(rollback.panel, line, config.limit);
+        }
+        if let (Ok(()), Some(service)) = (result, rollback.failed) {
+            App::start_service(logs, config, service, Trigger::Restart).await;
+        }
+    }
+    async fn running_services(&self) -> Vec<MenuCommand> {
+        let logs = self.logs.read().await;
+        self.config
+            .service_order()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|&svc| CommandMode::of(svc).is_some_and(|m| logs.service(m).isrunning))
+            .collect()
     }
-    fn run_quick_command(&mut self, cmd: String, args: Vec<String>) {
+    /// quits right away when nothing is running, otherwise applies `policy`
+    async fn request_exit(&mut self, policy: OnExit) {
+        if self.shutdown.is_some() {
+            return;
+        }
+        let running = self.running_services().await;
+        if running.is_empty() {
+            self.should_exit = true;
+            return;
+        }
+        match policy {
+            OnExit::Stop => self.stop_all_and_exit(),
+            OnExit::Detach => self.should_exit = true,
+            OnExit::Ask => {
+                let mut lines = vec!["These services are still running:".to_string()];
+                lines.extend(running.iter().map(|svc| format!("  {:?}", svc)));
+                self.dialog = Some(
+                    Dialog::new("Quit", lines)
+                        .option('s', "stop them and quit", DialogAction::QuitStop)
+                        .option('d', "leave them running and quit", DialogAction::QuitDetach),
+                );
+            }
+        }
+    }
+    /// stops every service, dependents first, the run loop exits once this is done
+    fn stop_all_and_exit(&mut self) {
         let logs = Arc::clone(&self.logs);
-
-        tokio::spawn(async move {
-            let mut debug = logs.write().await;
-            debug.debug_logs.push(args.join(" "));
-            let mut cmd = Command::new(cmd)
-                .args(args)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-                .expect("Failed to execute command");
-
-            let stdout = cmd.stdout.take().unwrap();
-            let stderr = cmd.stderr.take().unwrap();
-
-            let stdout = LinesStream::new(BufReader::new(stdout).lines());
-            let stderr = LinesStream::new(BufReader::new(stderr).lines());
-            let mut merged = StreamExt::merge(stdout, stderr);
-
-            while let Some(line) = merged.next().await {
-                let line = match line {
-                    Ok(l) => App::trim_line(l),
-                    Err(_) => String::from("----deducted---"),
-                };
-
-                debug.debug_logs.push(line);
+        let order = self.config.service_order().unwrap_or_default();
+        let limit = self.config.limit;
+        let handle = supervise(&self.logs, "shutdown", async move {
+            for svc in order.into_iter().rev() {
+                App::stop_service(Arc::clone(&logs), limit, svc).await;
             }
-            debug.debug_logs.push("Finished...".to_string());
         });
+        self.shutdown = Some(Arc::new(handle));
     }
-
-    fn change_status(&mut self, command: MenuCommand, status: Status) {
-        if let Some(whichis) = self
-            .menu_list
-            .items
-            .iter_mut()
-            .find(|item| item.cmd == command)
-        {
-            whichis.status = status;
+    /// stops `service`, asking first when running services depend on it
+    async fn stop_with_dependents(&mut self, service: MenuCommand) {
+        let running = {
+            let logs = self.logs.read().await;
+            self.config
+                .dependents_of(service)
+                .into_iter()
+                .filter(|&svc| CommandMode::of(svc).is_some_and(|m| logs.service(m).isrunning))
+                .collect::<Vec<_>>()
+        };
+        if running.is_empty() {
+            let task = App::stop_service(Arc::clone(&self.logs), self.config.limit, service);
+            supervise(&self.logs, "stop_service", task);
+            return;
         }
+        let mut lines = vec![format!("These services depend on {:?}:", service)];
+        lines.extend(running.iter().map(|svc| format!("  {:?}", svc)));
+        self.dialog = Some(
+            Dialog::new("Stop service", lines)
+                .option(
+                    'y',
+                    "stop them too",
+                    DialogAction::StopWithDependents(service),
+                )
+                .option('n', "stop only this one", DialogAction::Stop(service)),
+        );
     }
-
-    async fn process_menu(&mut self, index: usize, terminal: &mut DefaultTerminal) -> Result<()> {
-        let mut menuinfo = self.menu_list.items[index].clone();
+    fn process_dialog(&mut self, action: DialogAction) {
         let logs = Arc::clone(&self.logs);
-        let this = self.clone();
-        let menucommand = menuinfo.cmd;
-        match menucommand {
-            MenuCommand::ComfyRun => {
-                self.change_status(MenuCommand::ComfyRun, Status::Run);
-                tokio::spawn(async move {
-                    let mut logs = logs.write().await;
-                    menuinfo.status = Status::Run;
-                    if !logs.comfyui_isrunning {
-                        this.run_command(CommandMode::COMFYUI, MenuCommand::ComfyRun);
-                        logs.comfyui_isrunning = true;
-                    }
-                });
+        match action {
+            DialogAction::Stop(service) => {
+                supervise(
+                    &self.logs,
+                    "stop_service",
+                    App::stop_service(logs, self.config.limit, service),
+                );
             }
-            MenuCommand::ComfyKill => {
-                self.change_status(MenuCommand::ComfyRun, Status::Idle);
-                tokio::spawn(async move {
-                    let mut logs = logs.write().await;
-                    if let Some(sender) = logs.comfyui_pid {
-                        logs.comfyui_logs.clear();
-                        logs.comfyui_logs
-                            .push(format!("Sending Exit Signal :{}", sender));
-                        match App::kill_command(sender) {
-                            Ok(_) => {
-                                logs.comfyui_logs.push(format!("Success :{}", sender));
-                                logs.comfyui_isrunning = false;
-                            }
-                            Err(err) => {
-                                logs.comfyui_isrunning = false;
-                                logs.comfyui_logs
-                                    .push(format!("Failed to terminate pid :{}", err));
-                            }
-                        }
-                    }
+            DialogAction::QuitStop => self.stop_all_and_exit(),
+            DialogAction::SaveForm => self.save_form(true),
+            DialogAction::SaveCommand => self.save_editor(),
+            DialogAction::DiscardCommand => {
+                self.editor = None;
+                self.view = View::Logs;
+            }
+            DialogAction::DeleteCommand(index) => {
+                let mut commands = self.config.commands.clone();
+                let removed = commands.remove(index);
+                let line = match self.apply_commands(commands, index.saturating_sub(1)) {
+                    Ok(()) => format!("Deleted {} from the menu", removed.name.trim()),
+                    Err(err) => format!("[error] {} not deleted :{}", removed.name.trim(), err),
+                };
+                supervise(&self.logs, "delete_command", async move {
+                    logs.write().await.debug_logs.push(line);
                 });
             }
-            MenuCommand::ComfyUpdate => {
-                this.run_command(CommandMode::COMFYUI, MenuCommand::ComfyUpdate);
+            DialogAction::DiscardForm => {
+                self.form = None;
+                self.view = View::Logs;
             }
-            MenuCommand::CronRun => {
-                self.change_status(MenuCommand::CronRun, Status::Run);
-                tokio::spawn(async move {
-                    let mut logs = logs.write().await;
-                    menuinfo.status = Status::Run;
-                    if !logs.cron_isrunning {
-                        this.run_command(CommandMode::CRON, MenuCommand::CronRun);
-                        logs.cron_isrunning = true;
+            DialogAction::RestorePackages(target) => {
+                let task = App::restore_packages(logs, self.config.clone(), target);
+                supervise(&self.logs, "restore_packages", task);
+            }
+            DialogAction::Rollback => {
+                let task = App::rollback(logs, self.config.clone());
+                supervise(&self.logs, "rollback", task);
+            }
+            DialogAction::CancelPipeline(key) => {
+                supervise(&self.logs, "cancel_pipeline", async move {
+                    if let Some(pipeline) = logs.read().await.pipelines.get(&key) {
+                        pipeline.cancel();
                     }
                 });
             }
-            MenuCommand::CronKill => {
-                self.change_status(MenuCommand::CronRun, Status::Idle);
-                tokio::spawn(async move {
+            DialogAction::Signal(pid, signal) => {
+                let result = explorer::send_signal(pid, signal);
+                supervise(&self.logs, "signal", async move {
                     let mut logs = logs.write().await;
-                    if let Some(sender) = logs.cron_pid {
-                        logs.cron_logs.clear();
-                        logs.cron_logs
-                            .push(format!("Sending Exit Signal :{}", sender));
-                        match App::kill_command(sender) {
-                            Ok(_) => {
-                                logs.cron_logs.push(format!("Success :{}", sender));
-                                logs.cron_isrunning = false;
-                            }
-                            Err(err) => {
-                                logs.cron_isrunning = false;
-                                logs.cron_logs
-                                    .push(format!("Failed to terminate pid :{}", err));
+                    let line = match &result {
+                        Ok(()) => format!("Sent {:?} to {}", signal, pid),
+                        Err(err) => format!("[error] {}", err),
+                    };
+                    logs.debug_logs.push(line);
+                    if result.is_err() {
+                        return;
+                    }
+                    // keep the menu honest when the picker froze or resumed a service
+                    for mode in [CommandMode::Comfyui, CommandMode::Cron] {
+                        let state = logs.service_mut(mode);
+                        if state.pid == Some(pid) {
+                            match signal {
+                                Signal::Stop => state.paused = true,
+                                Signal::Continue => state.paused = false,
+                                _ => {}
                             }
                         }
                     }
                 });
             }
-            MenuCommand::Config => {
-                let _ = self.run_editor(terminal).await;
-
-                //self.debug = self.config.to_string();
+            DialogAction::QuitDetach => self.should_exit = true,
+            DialogAction::StopWithDependents(service) => {
+                let mut order = self.config.dependents_of(service);
+                order.push(service);
+                let limit = self.config.limit;
+                supervise(&self.logs, "stop_service", async move {
+                    for svc in order {
+                        App::stop_service(Arc::clone(&logs), limit, svc).await;
+                    }
+                });
             }
-            MenuCommand::About => {
-                //let _ = self.run_editor(terminal).await;
+        }
+    }
 
-                //self.debug = self.config.to_string();
-            }
-            MenuCommand::Exit => {
-                self.should_exit = true;
-            }
+    /// the service of the focused log panel, or of the selected menu entry
+    fn focused_service(&self) -> Option<MenuCommand> {
+        if let Some(mode) = self.active_panel.mode() {
+            return Some(mode.service());
+        }
+        let item = &self.menu_list.items[self.menu_list.state.selected()?];
+        if item.cmd.is_service() {
+            Some(item.cmd)
+        } else {
+            item.cmd.service_of()
         }
-        Ok(())
     }
     fn clear_log_panel(&self) {
         let log = Arc::clone(&self.logs);
-        tokio::spawn(async move {
+        supervise(&self.logs, "clear_log_panel", async move {
             let mut logs = log.write().await;
-            logs.comfyui_logs.clear();
-            logs.cron_logs.clear();
+            logs.comfyui.logs.clear();
+            logs.cron.logs.clear();
             logs.debug_logs.clear();
         });
     }
@@ -407,64 +2810,538 @@ impl App {
         terminal.clear()?;
         Ok(())
     }
+    fn toggle_view(&mut self, view: View) {
+        self.view = if self.view == view { View::Logs } else { view };
+    }
+    /// keys of the process table, returns false for keys it leaves to the rest of the app
+    fn handle_explorer_key(&mut self, key: &KeyEvent) -> bool {
+        if self.explorer.filtering {
+            match self.explorer.filter.handle_key(key) {
+                InputEvent::Submit(line) => {
+                    self.explorer.filter.buffer = line;
+                    self.explorer.filtering = false;
+                }
+                InputEvent::Cancel => {
+                    self.explorer.filter.buffer.clear();
+                    self.explorer.filtering = false;
+                }
+                InputEvent::Eof | InputEvent::Edited => {}
+            }
+            return true;
+        }
+        match key.code {
+            KeyCode::Down => self.explorer.select(1),
+            KeyCode::Up => self.explorer.select(-1),
+            KeyCode::PageDown => self.explorer.select(20),
+            KeyCode::PageUp => self.explorer.select(-20),
+            KeyCode::Char('/') => self.explorer.filtering = true,
+            KeyCode::Esc => self.view = View::Logs,
+            KeyCode::Char('s') => {
+                if let Some(row) = self.explorer.selected() {
+                    self.dialog = Some(App::signal_dialog(row.pid, &row.name));
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+    /// keys of the custom nodes table, returns false for keys it leaves to the rest of the app
+    async fn handle_nodes_key(&mut self, key: &KeyEvent) -> bool {
+        let logs = Arc::clone(&self.logs);
+        let config = self.config.clone();
+        match key.code {
+            KeyCode::Down | KeyCode::Up => {
+                let step = if key.code == KeyCode::Down { 1 } else { -1 };
+                self.nodes.select(&logs.read().await.nodes, step);
+            }
+            KeyCode::Char('u') => {
+                let selected = self
+                    .nodes
+                    .selected(&logs.read().await.nodes)
+                    .map(|r| r.path.clone());
+                if let Some(path) = selected {
+                    let task = App::update_nodes(logs, config, vec![path]);
+                    supervise(&self.logs, "update_nodes", task);
+                }
+            }
+            KeyCode::Char('a') => {
+                let paths = logs
+                    .read()
+                    .await
+                    .nodes
+                    .iter()
+                    .map(|r| r.path.clone())
+                    .collect();
+                let task = App::update_nodes(logs, config, paths);
+                supervise(&self.logs, "update_nodes", task);
+            }
+            KeyCode::Char('f') => {
+                supervise(
+                    &self.logs,
+                    "scan_nodes",
+                    App::scan_nodes(logs, config, true),
+                );
+            }
+            KeyCode::Esc => self.view = View::Logs,
+            _ => return false,
+        }
+        true
+    }
+    /// opens the args form of the selected menu entry, one-shot steps and built-ins have none
+    fn open_form(&mut self) {
+        let selected = self
+            .menu_list
+            .state
+            .selected()
+            .and_then(|i| self.config.commands.get(i))
+            .filter(|c| !c.exe_path.is_empty() && c.steps.is_empty());
+        if let Some(cmd) = selected {
+            self.form = Some(ArgsForm::new(cmd));
+            self.view = View::Args;
+        }
+    }
+    fn handle_form_key(&mut self, key: &KeyEvent) {
+        let Some(form) = self.form.as_mut() else {
+            return;
+        };
+        match form.handle_key(key) {
+            FormEvent::Save => self.save_form(false),
+            FormEvent::Close if form.changed => {
+                self.dialog = Some(
+                    Dialog::new(
+                        "Unsaved arguments",
+                        vec![format!("{} has changes that are not saved", form.name)],
+                    )
+                    .option('s', "save to tool.json", DialogAction::SaveForm)
+                    .option('d', "discard them", DialogAction::DiscardForm),
+                );
+            }
+            FormEvent::Close => {
+                self.form = None;
+                self.view = View::Logs;
+            }
+            FormEvent::Edited => {}
+        }
+    }
+    /// writes the form's args to tool.json and the loaded config
+    fn save_form(&mut self, close: bool) {
+        let Some(form) = self.form.as_mut() else {
+            return;
+        };
+        let args = form.args();
+        let line = match Config::save_args(&form.key, &args) {
+            Ok(()) => {
+                form.changed = false;
+                for cmd in self
+                    .config
+                    .commands
+                    .iter_mut()
+                    .filter(|c| c.key() == form.key)
+                {
+                    cmd.args = args.clone();
+                }
+                self.shared_config.send_replace(self.config.clone());
+                format!("Saved args of {}, used from the next start", form.name)
+            }
+            Err(err) => format!("[error] Saving args of {} failed :{}", form.name, err),
+        };
+        if close && !form.changed {
+            self.form = None;
+            self.view = View::Logs;
+        }
+        let logs = Arc::clone(&self.logs);
+        supervise(&self.logs, "save_form", async move {
+            logs.write().await.debug_logs.push(line);
+        });
+    }
+    /// keys of the menu panel that change the entries, returns false for the others
+    fn handle_menu_edit_key(&mut self, key: &KeyEvent) -> bool {
+        let selected = self.menu_list.state.selected();
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match (key.code, selected) {
+            (KeyCode::Char('e'), Some(i)) => {
+                self.editor = Some(CommandEditor::edit(&self.config.commands[i], i));
+                self.view = View::Command;
+            }
+            (KeyCode::Char('+') | KeyCode::Insert, _) => {
+                let at = selected.map_or(self.config.commands.len(), |i| i + 1);
+                self.editor = Some(CommandEditor::new(at));
+                self.view = View::Command;
+            }
+            (KeyCode::Delete, Some(i)) => {
+                let name = self.config.commands[i].name.trim().to_string();
+                self.dialog = Some(
+                    Dialog::new(
+                        "Delete menu entry",
+                        vec![format!("{} is removed from tool.json", name)],
+                    )
+                    .option('y', "delete it", DialogAction::DeleteCommand(i)),
+                );
+            }
+            (KeyCode::Up | KeyCode::Down, Some(i)) if shift => {
+                let to = if key.code == KeyCode::Up {
+                    i.checked_sub(1)
+                } else {
+                    Some(i + 1).filter(|&to| to < self.config.commands.len())
+                };
+                let Some(to) = to else {
+                    return true;
+                };
+                let mut commands = self.config.commands.clone();
+                commands.swap(i, to);
+                if let Err(err) = self.apply_commands(commands, to) {
+                    let logs = Arc::clone(&self.logs);
+                    let line = format!("[error] Menu order not saved :{}", err);
+                    supervise(&self.logs, "move_command", async move {
+                        logs.write().await.debug_logs.push(line);
+                    });
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+    fn handle_editor_key(&mut self, key: &KeyEvent) {
+        let Some(editor) = self.editor.as_mut() else {
+            return;
+        };
+        match editor.handle_key(key) {
+            FormEvent::Save => self.save_editor(),
+            FormEvent::Close if editor.changed => {
+                self.dialog = Some(
+                    Dialog::new(
+                        "Unsaved menu entry",
+                        vec![format!("{} has changes that are not saved", editor.name())],
+                    )
+                    .option('s', "save to tool.json", DialogAction::SaveCommand)
+                    .option('d', "discard them", DialogAction::DiscardCommand),
+                );
+            }
+            FormEvent::Close => {
+                self.editor = None;
+                self.view = View::Logs;
+            }
+            FormEvent::Edited => {}
+        }
+    }
+    /// saves the entry of the editor and closes it, a refused entry keeps it open with the reason
+    fn save_editor(&mut self) {
+        let Some(editor) = self.editor.as_ref() else {
+            return;
+        };
+        let cmd = editor.cmd();
+        let name = editor.name().to_string();
+        let mut commands = self.config.commands.clone();
+        let index = match editor.index {
+            Some(i) => {
+                commands[i] = cmd;
+                i
+            }
+            None => {
+                let at = editor.insert_at.min(commands.len());
+                commands.insert(at, cmd);
+                at
+            }
+        };
+        if let Err(err) = self.apply_commands(commands, index) {
+            if let Some(editor) = self.editor.as_mut() {
+                editor.error = Some(err);
+            }
+            return;
+        }
+        self.editor = None;
+        self.view = View::Logs;
+        let logs = Arc::clone(&self.logs);
+        supervise(&self.logs, "save_command", async move {
+            logs.write()
+                .await
+                .debug_logs
+                .push(format!("Saved {} to the menu", name));
+        });
+    }
+    /// checks `commands` like a fresh start would, writes them to tool.json and rebuilds the menu
+    fn apply_commands(
+        &mut self,
+        commands: Vec<CmdInfo>,
+        select: usize,
+    ) -> std::result::Result<(), String> {
+        let mut config = self.config.clone();
+        config.commands = commands;
+        config.check().map_err(|err| err.to_string())?;
+        Config::save_commands(&config.commands).map_err(|err| err.to_string())?;
+        self.menu_list.items = config
+            .commands
+            .iter()
+            .map(|c| MenuInfo::new(c.command, &c.name, &c.desc, Status::Idle))
+            .collect();
+        self.menu_list
+            .state
+            .select(Some(select.min(config.commands.len().saturating_sub(1))));
+        self.shared_config.send_replace(config.clone());
+        self.config = config;
+        Ok(())
+    }
+    /// keys of the run history, newest first
+    async fn handle_history_key(&mut self, key: &KeyEvent) -> bool {
+        let count = self.history.runs.len() + self.logs.read().await.runs.len();
+        match key.code {
+            KeyCode::Down => self.history.select(1, count),
+            KeyCode::Up => self.history.select(-1, count),
+            KeyCode::PageDown => self.history.select(20, count),
+            KeyCode::PageUp => self.history.select(-20, count),
+            KeyCode::Char('f') => self.history.reload(),
+            KeyCode::Esc => self.view = View::Logs,
+            _ => return false,
+        }
+        true
+    }
+    /// keys of the jobs panel, newest first
+    async fn handle_jobs_key(&mut self, key: &KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Down | KeyCode::Up => {
+                let count = self.logs.read().await.jobs.list.len();
+                let step = if key.code == KeyCode::Down { 1 } else { -1 };
+                self.jobs.select(step, count);
+            }
+            KeyCode::Char('x') | KeyCode::Delete => {
+                let mut logs = self.logs.write().await;
+                let Some(id) = self.jobs.selected(&logs.jobs) else {
+                    return true;
+                };
+                if !logs.jobs.cancel(id) {
+                    logs.debug_logs
+                        .push(format!("[warn] job {} has already ended", id));
+                }
+            }
+            KeyCode::Esc => self.view = View::Logs,
+            _ => return false,
+        }
+        true
+    }
+    async fn handle_alerts_key(&mut self, key: &KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Down | KeyCode::Up => {
+                let count = self.logs.read().await.alerts.list.len();
+                let step = if key.code == KeyCode::Down { 1 } else { -1 };
+                self.alerts.select(step, count);
+            }
+            KeyCode::Char('c') => self.logs.write().await.alerts.clear(),
+            KeyCode::Esc => self.view = View::Logs,
+            _ => return false,
+        }
+        true
+    }
+    /// keys of the package snapshots, the list shows the newest first
+    fn handle_packages_key(&mut self, key: &KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Down => self.packages.select(-1),
+            KeyCode::Up => self.packages.select(1),
+            KeyCode::Char('f') => self.packages.reload(),
+            KeyCode::Char('b') => {
+                if let Some(target) = self.packages.selected().cloned() {
+                    self.dialog = Some(
+                        Dialog::new(
+                            "Restore packages",
+                            vec![
+                                format!(
+                                    "{:?} back to {} UTC, {} packages",
+                                    target.service,
+                                    format_time(target.taken / 1000),
+                                    target.packages.len()
+                                ),
+                                target.label.clone(),
+                                "the service is stopped while pip runs".to_string(),
+                            ],
+                        )
+                        .option(
+                            'y',
+                            "restore",
+                            DialogAction::RestorePackages(target),
+                        ),
+                    );
+                }
+            }
+            KeyCode::Esc => self.view = View::Logs,
+            _ => return false,
+        }
+        true
+    }
+    fn signal_dialog(pid: u32, name: &str) -> Dialog<DialogAction> {
+        let signals = [
+            ('t', Signal::Term),
+            ('k', Signal::Kill),
+            ('i', Signal::Interrupt),
+            ('h', Signal::Hangup),
+            ('1', Signal::User1),
+            ('2', Signal::User2),
+            ('s', Signal::Stop),
+            ('c', Signal::Continue),
+        ];
+        signals.into_iter().fold(
+            Dialog::new("Send signal", vec![format!("{} ({})", name, pid)]),
+            |dialog, (key, signal)| {
+                dialog.option(
+                    key,
+                    &format!("{:?}", signal),
+                    DialogAction::Signal(pid, signal),
+                )
+            },
+        )
+    }
+    /// log lines scroll from the top, a terminal scrolls back from its bottom
+    async fn scroll_log(&mut self, mode: CommandMode, up: bool) {
+        let back = up == self.logs.read().await.service(mode).pty.is_none();
+        let (scroll, scrollbar) = match mode {
+            CommandMode::Comfyui => (
+                &mut self.comfylog_scroll,
+                &mut self.comfylog_scrollbar_state,
+            ),
+            CommandMode::Cron => (&mut self.cronlog_scroll, &mut self.cronlog_scrollbar_state),
+        };
+        *scroll = if back {
+            scroll.saturating_sub(1)
+        } else {
+            scroll.saturating_add(1)
+        };
+        *scrollbar = scrollbar.position(*scroll);
+    }
+    /// runs the command once every param has a value, the values are kept for next time
+    async fn handle_prompt_key(&mut self, key: &KeyEvent, terminal: &mut DefaultTerminal) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        match prompt.handle_key(key, &mut self.recent) {
+            PromptEvent::Done(cmdinfo) => {
+                let index = prompt.index;
+                self.prompt = None;
+                if let Err(err) = self.recent.save() {
+                    self.logs
+                        .write()
+                        .await
+                        .debug_logs
+                        .push(format!("[warn] recent param values not saved :{}", err));
+                }
+                self.selected_config = Some(*cmdinfo);
+                let _ = self.process_menu(index, terminal).await;
+            }
+            PromptEvent::Cancel => self.prompt = None,
+            PromptEvent::Edited => {}
+        }
+    }
     async fn handle_events(&mut self, event: &Event, terminal: &mut DefaultTerminal) {
         if let Event::Key(key) = event {
+            if key.kind == KeyEventKind::Press && self.dialog.is_some() {
+                match key.code {
+                    KeyCode::Esc => self.dialog = None,
+                    KeyCode::Char(c) => {
+                        if let Some(action) = self.dialog.as_ref().and_then(|d| d.action(c)) {
+                            self.dialog = None;
+                            self.process_dialog(action);
+                        }
+                    }
+                    _ => {}
+                }
+                return;
+            }
+            if key.kind == KeyEventKind::Press && self.prompt.is_some() {
+                self.handle_prompt_key(key, terminal).await;
+                return;
+            }
             if key.kind == KeyEventKind::Press {
+                if let Some(mode) = self.attached {
+                    let session = self.logs.read().await.service(mode).pty.clone();
+                    if let Some(session) = session {
+                        // Ctrl-] detaches, everything else belongs to the program
+                        let detach = key.modifiers.contains(KeyModifiers::CONTROL)
+                            && matches!(key.code, KeyCode::Char(']') | KeyCode::Char('5'));
+                        if detach {
+                            self.attached = None;
+                        } else if let Some(bytes) = session.encode_key(key) {
+                            let _ = session.write(&bytes);
+                        }
+                        return;
+                    }
+                    match self.input.handle_key(key) {
+                        InputEvent::Submit(line) => {
+                            self.send_stdin(mode, StdinMsg::Line(line)).await
+                        }
+                        InputEvent::Eof => self.send_stdin(mode, StdinMsg::Eof).await,
+                        InputEvent::Cancel => self.attached = None,
+                        InputEvent::Edited => {}
+                    }
+                    return;
+                }
+                if self.view == View::Processes && self.handle_explorer_key(key) {
+                    return;
+                }
+                if self.view == View::Nodes && self.handle_nodes_key(key).await {
+                    return;
+                }
+                if self.view == View::Packages && self.handle_packages_key(key) {
+                    return;
+                }
+                if self.view == View::History && self.handle_history_key(key).await {
+                    return;
+                }
+                if self.view == View::Alerts && self.handle_alerts_key(key).await {
+                    return;
+                }
+                if self.view == View::Jobs && self.handle_jobs_key(key).await {
+                    return;
+                }
+                if self.view == View::Args && self.form.is_some() {
+                    self.handle_form_key(key);
+                    return;
+                }
+                if self.view == View::Command && self.editor.is_some() {
+                    self.handle_editor_key(key);
+                    return;
+                }
+                if self.active_panel == ActivePanel::Menu && self.handle_menu_edit_key(key) {
+                    return;
+                }
                 match key.code {
-                    KeyCode::Char('q') => self.should_exit = true,
+                    KeyCode::Char('q') => {
+                        if self.shutdown.is_some() {
+                            // second press while stopping, give up waiting
+                            self.should_exit = true;
+                        } else {
+                            self.request_exit(self.config.on_exit).await;
+                        }
+                    }
                     KeyCode::Char('d') => {
-                        self.show_debugconsole = !self.show_debugconsole;
+                        self.toggle_view(View::Debug);
                     }
-                    KeyCode::Char('t') => {
-                        self.run_quick_command(
-                            "pwsh".to_string(),
-                            vec![
-                                "-NoProfile",
-                                "-Command",
-                                "tasklist",
-                                "|",
-                                "rg",
-                                "\"deno*|python*\"",
-                            ]
-                            .iter()
-                            .map(|m| m.to_string())
-                            .collect(),
-                        );
+                    KeyCode::Char('p') => {
+                        self.toggle_view(View::Processes);
                     }
-                    KeyCode::Char('k') => {
-                        self.run_quick_command(
-                            "pwsh".to_string(),
-                            vec![
-                                "-NoProfile",
-                                "-Command",
-                                "taskkill",
-                                "/f",
-                                "/im",
-                                "deno*",
-                                "&&",
-                                "taskkill",
-                                "/f",
-                                "/im",
-                                "python*",
-                            ]
-                            .iter()
-                            .map(|m| m.to_string())
-                            .collect(),
-                        );
+                    KeyCode::Char('a') => self.open_form(),
+                    KeyCode::Char('j') => self.toggle_view(View::Jobs),
+                    KeyCode::Char('l') => self.toggle_view(View::Alerts),
+                    KeyCode::Char('h') => {
+                        if self.view == View::History {
+                            self.view = View::Logs;
+                        } else {
+                            self.history.reload();
+                            self.view = View::History;
+                        }
+                    }
+                    KeyCode::Char('n') => {
+                        if self.view == View::Nodes {
+                            self.view = View::Logs;
+                        } else {
+                            self.open_nodes();
+                        }
                     }
                     KeyCode::Down => match self.active_panel {
                         ActivePanel::Menu => {
                             self.menu_list.state.select_next();
                         }
                         ActivePanel::ComfyLog => {
-                            self.comfylog_scroll = self.comfylog_scroll.saturating_add(1);
-                            self.comfylog_scrollbar_state =
-                                self.comfylog_scrollbar_state.position(self.comfylog_scroll);
+                            self.scroll_log(CommandMode::Comfyui, false).await;
                         }
                         ActivePanel::CronLog => {
-                            self.cronlog_scroll = self.cronlog_scroll.saturating_add(1);
-                            self.cronlog_scrollbar_state =
-                                self.cronlog_scrollbar_state.position(self.cronlog_scroll);
+                            self.scroll_log(CommandMode::Cron, false).await;
                         }
                         ActivePanel::DebugLog => {}
                     },
@@ -473,14 +3350,10 @@ impl App {
                             self.menu_list.state.select_previous();
                         }
                         ActivePanel::ComfyLog => {
-                            self.comfylog_scroll = self.comfylog_scroll.saturating_sub(1);
-                            self.comfylog_scrollbar_state =
-                                self.comfylog_scrollbar_state.position(self.comfylog_scroll);
+                            self.scroll_log(CommandMode::Comfyui, true).await;
                         }
                         ActivePanel::CronLog => {
-                            self.cronlog_scroll = self.cronlog_scroll.saturating_sub(1);
-                            self.cronlog_scrollbar_state =
-                                self.cronlog_scrollbar_state.position(self.cronlog_scroll);
+                            self.scroll_log(CommandMode::Cron, true).await;
                         }
                         ActivePanel::DebugLog => {}
                     },
@@ -493,10 +3366,30 @@ impl App {
                     KeyCode::Tab => {
                         self.active_panel = self.active_panel.next();
                     }
+                    KeyCode::Char('i') => {
+                        if let Some(mode) = self.active_panel.mode() {
+                            if self.view == View::Logs {
+                                self.attached = Some(mode);
+                            }
+                        }
+                    }
 
                     KeyCode::Char('c') => {
                         self.clear_log_panel();
                     }
+                    KeyCode::Char('r') => {
+                        if let Some(service) = self.focused_service() {
+                            let logs = Arc::clone(&self.logs);
+                            let task = App::restart_service(logs, self.config.clone(), service);
+                            supervise(&self.logs, "restart_service", task);
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        let action = self.config.quick_actions.iter().find(|a| a.key == c);
+                        if let Some(action) = action.cloned() {
+                            self.run_quick_command(action);
+                        }
+                    }
 
                     _ => {}
                 }
@@ -505,7 +3398,12 @@ impl App {
     }
 
     fn set_title<'a>(&self, title: &'a str, active_panel: ActivePanel) -> Block<'a> {
-        if self.active_panel == active_panel {
+        if self.flashing.contains(&active_panel) {
+            Block::new()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::new().yellow().bold())
+        } else if self.active_panel == active_panel {
             Block::new()
                 .title(title)
                 .borders(Borders::ALL)
@@ -515,6 +3413,33 @@ impl App {
         }
     }
 
+    fn render_pty(
+        &self,
+        session: &PtySession,
+        title: &str,
+        panel: ActivePanel,
+        scroll: usize,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let block = self.set_title(title, panel);
+        let inner = block.inner(area);
+        block.render(area, buf);
+        session.scroll(scroll);
+        session.render(inner, buf);
+    }
+    /// draws the stdin line under an attached log panel and returns what is left for the logs
+    fn input_area(&self, mode: CommandMode, area: Rect, buf: &mut Buffer, pty: bool) -> Rect {
+        // a terminal takes the keys directly, no input line needed
+        if self.attached != Some(mode) || pty {
+            return area;
+        }
+        let [logs, input] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(3)]).areas(area);
+        self.input.render("stdin", input, buf);
+        logs
+    }
+
     fn render_list(&mut self, area: Rect, buf: &mut Buffer) {
         let block = self.set_title("Menu", ActivePanel::Menu);
         let menu: Vec<ListItem> = self
@@ -526,7 +3451,9 @@ impl App {
                 let color = state_color(i);
                 match menu.status {
                     Status::Idle => ListItem::from(menu).fg(color),
+                    Status::Starting => ListItem::from(menu).fg(color).bg(Color::Yellow),
                     Status::Run => ListItem::from(menu).fg(color).bg(Color::Red),
+                    Status::Paused => ListItem::from(menu).fg(color).bg(Color::Blue),
                 }
             })
             .collect();
@@ -550,28 +3477,103 @@ version  :{}"#,
             env!("CARGO_PKG_VERSION")
         )
     }
-    fn render_selected_menu(&self, area: Rect, buf: &mut Buffer) {
-        let info = if let Some(i) = self.menu_list.state.selected() {
-            if self.menu_list.items[i].cmd == MenuCommand::About {
-                self.about()
-            } else {
-                format!("{}", self.menu_list.items[i].info)
-            }
-        } else {
-            self.about()
+    fn render_selected_menu(&self, logs: &LogLists, area: Rect, buf: &mut Buffer) {
+        let selected = self
+            .menu_list
+            .state
+            .selected()
+            .map(|i| &self.menu_list.items[i]);
+        let mut info = match selected {
+            Some(item) if item.cmd != MenuCommand::About => item.info.clone(),
+            _ => self.about(),
         };
+        let state = selected
+            .filter(|item| item.cmd.is_service() || item.cmd.service_of().is_some())
+            .and_then(|item| CommandMode::of(item.cmd))
+            .map(|mode| logs.service(mode));
+        let entry = self
+            .menu_list
+            .state
+            .selected()
+            .and_then(|i| self.config.commands.get(i));
+        if let Some(entry) = entry {
+            let steps = match logs.pipelines.get(&entry.key()) {
+                Some(pipeline) => pipeline.lines(),
+                None => entry
+                    .steps
+                    .iter()
+                    .map(|s| format!("· {}", s.label()))
+                    .collect(),
+            };
+            if !steps.is_empty() {
+                info.push_str("\nsteps:");
+                for line in steps {
+                    info.push('\n');
+                    info.push_str(&line);
+                }
+            }
+        }
+        if let Some(state) = state.filter(|state| !state.limits.is_empty()) {
+            info.push_str("\nlimits:");
+            for line in &state.limits {
+                info.push_str("\n  ");
+                info.push_str(line);
+            }
+        }
         // We show the list item's info under the list in this paragraph
         let block = Block::new()
             .title("Info")
             .borders(Borders::ALL)
             .padding(Padding::horizontal(1));
+        let inner = block.inner(area);
+        block.render(area, buf);
 
         // We can now render the item info
-        Paragraph::new(info)
-            .block(block)
+        let info = Paragraph::new(info)
             .fg(Color::Red)
-            .wrap(Wrap { trim: true })
-            .render(area, buf);
+            .wrap(Wrap { trim: true });
+        // services and their kill commands also get the usage graphs
+        let usage = state
+            .map(|state| &state.usage)
+            .filter(|usage| !usage.cpu.is_empty());
+        let Some(usage) = usage else {
+            info.render(inner, buf);
+            return;
+        };
+        let [text, stats, cpu, rss] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .areas(inner);
+        info.render(text, buf);
+        let line = match usage.current {
+            Some(sample) => format!(
+                "cpu {:.1}% | rss {} | threads {}",
+                sample.cpu,
+                format_bytes(sample.rss),
+                sample.threads
+            ),
+            None => "not running".to_string(),
+        };
+        Paragraph::new(line).bold().render(stats, buf);
+        // newest samples on the right
+        let width = cpu.width as usize;
+        let cpu_max = usage.cpu.iter().copied().max().unwrap_or_default().max(100);
+        Sparkline::default()
+            .block(Block::new().title(format!("cpu % (max {})", cpu_max)))
+            .data(usage.cpu.iter().skip(usage.cpu.len().saturating_sub(width)))
+            .max(cpu_max)
+            .fg(Color::Yellow)
+            .render(cpu, buf);
+        let rss_max = usage.rss.iter().copied().max().unwrap_or_default().max(1);
+        Sparkline::default()
+            .block(Block::new().title(format!("rss MB (max {})", rss_max)))
+            .data(usage.rss.iter().skip(usage.rss.len().saturating_sub(width)))
+            .max(rss_max)
+            .fg(Color::Cyan)
+            .render(rss, buf);
     }
 }
 impl Widget for &mut App {
@@ -586,20 +3588,36 @@ impl Widget for &mut App {
         let rights =
             Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).split(right);
 
+        let shared = Arc::clone(&self.logs);
+        let logs =
+            task::block_in_place(|| tokio::runtime::Handle::current().block_on(shared.read()));
+        for item in self.menu_list.items.iter_mut() {
+            if item.cmd.is_service() {
+                if let Some(mode) = CommandMode::of(item.cmd) {
+                    item.status = logs.service(mode).status();
+                }
+            }
+        }
+        self.flashing = [
+            (ActivePanel::ComfyLog, &logs.comfyui),
+            (ActivePanel::CronLog, &logs.cron),
+        ]
+        .into_iter()
+        .filter(|(_, state)| state.flashing())
+        .map(|(panel, _)| panel)
+        .collect();
+
         //Render Menus
         self.render_list(lefts[0], buf);
         //Render menu info
-        self.render_selected_menu(lefts[1], buf);
-        let logs = task::block_in_place(|| {
-            let logs = tokio::runtime::Handle::current().block_on(self.logs.read());
-            logs
-        });
-        let comfylog_len = logs.comfyui_logs.len();
-        let cronlog_len = logs.cron_logs.len();
+        self.render_selected_menu(&logs, lefts[1], buf);
+        let comfylog_len = logs.comfyui.logs.len();
+        let cronlog_len = logs.cron.logs.len();
 
         //Render ComfyLog Log
         let comfyui_log_lines = logs
-            .comfyui_logs
+            .comfyui
+            .logs
             .iter()
             .map(|s| Line::from(Span::raw(s.clone())))
             .collect::<Vec<_>>();
@@ -611,7 +3629,8 @@ impl Widget for &mut App {
 
         //Render Cron Log
         let cron_log_lines = logs
-            .cron_logs
+            .cron
+            .logs
             .iter()
             .map(|s| Line::from(Span::raw(s.clone())))
             .collect::<Vec<_>>();
@@ -633,11 +3652,61 @@ impl Widget for &mut App {
             .begin_symbol(Some("↑"))
             .end_symbol(Some("↓"));
 
-        if self.show_debugconsole {
+        if self.view == View::Processes {
+            let managed = [logs.comfyui.pid, logs.cron.pid]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            let block = self.set_title("Processes", ActivePanel::DebugLog);
+            self.explorer.refresh();
+            self.explorer.render(block, &managed, right, buf);
+        } else if self.view == View::Nodes {
+            let title = format!("Custom Nodes ({})", logs.nodes.len());
+            let block = self.set_title(&title, ActivePanel::DebugLog);
+            self.nodes.render(block, &logs.nodes, right, buf);
+        } else if self.view == View::Packages {
+            let title = format!("Package Snapshots ({})", self.packages.snapshots.len());
+            let block = self.set_title(&title, ActivePanel::DebugLog);
+            self.packages.render(block, right, buf);
+        } else if self.view == View::History {
+            let title = format!("History ({} runs)", self.history.runs.len());
+            let block = self.set_title(&title, ActivePanel::DebugLog);
+            self.history.render(block, &logs.runs, right, buf);
+        } else if self.view == View::Jobs {
+            let title = format!(
+                "Jobs ({} of {} running, {} queued)",
+                logs.jobs.running(),
+                logs.jobs.max,
+                logs.jobs.queued()
+            );
+            let block = self.set_title(&title, ActivePanel::DebugLog);
+            self.jobs.render(block, &logs.jobs, right, buf);
+        } else if self.view == View::Alerts {
+            let title = format!("Alerts ({})", logs.alerts.list.len());
+            let block = self.set_title(&title, ActivePanel::DebugLog);
+            self.alerts.render(block, &logs.alerts, right, buf);
+        } else if let (View::Command, Some(editor)) = (self.view, &self.editor) {
+            let title = match editor.index {
+                Some(_) => format!("Edit {}", editor.name()),
+                None => "New menu entry".to_string(),
+            };
+            editor.render(self.set_title(&title, ActivePanel::DebugLog), right, buf);
+        } else if let (View::Args, Some(form)) = (self.view, &self.form) {
+            let title = format!("Arguments of {}", form.name);
+            form.render(self.set_title(&title, ActivePanel::DebugLog), right, buf);
+        } else if self.view == View::Debug {
             let debug = Paragraph::new(
                 logs.debug_logs
                     .iter()
-                    .map(|s| Line::from(Span::raw(s.clone())))
+                    .map(|s| {
+                        if s.starts_with("[error]") {
+                            Line::from(Span::raw(s.clone())).red()
+                        } else if s.starts_with("[warn]") {
+                            Line::from(Span::raw(s.clone())).yellow()
+                        } else {
+                            Line::from(Span::raw(s.clone()))
+                        }
+                    })
                     .collect::<Vec<_>>(),
             )
             .block(self.set_title("Debug Console", ActivePanel::DebugLog))
@@ -645,37 +3714,196 @@ impl Widget for &mut App {
             .left_aligned();
             debug.render(right, buf);
         } else {
-            comfyuilog_paragraph.render(rights[0], buf);
-            cronuilog_paragraph.render(rights[1], buf);
+            let comfy_area = self.input_area(
+                CommandMode::Comfyui,
+                rights[0],
+                buf,
+                logs.comfyui.pty.is_some(),
+            );
+            let cron_area =
+                self.input_area(CommandMode::Cron, rights[1], buf, logs.cron.pty.is_some());
+            match &logs.comfyui.pty {
+                Some(session) => self.render_pty(
+                    session,
+                    "ComfyUI Terminal",
+                    ActivePanel::ComfyLog,
+                    self.comfylog_scroll,
+                    comfy_area,
+                    buf,
+                ),
+                None => comfyuilog_paragraph.render(comfy_area, buf),
+            }
+            match &logs.cron.pty {
+                Some(session) => self.render_pty(
+                    session,
+                    "Cron Terminal",
+                    ActivePanel::CronLog,
+                    self.cronlog_scroll,
+                    cron_area,
+                    buf,
+                ),
+                None => cronuilog_paragraph.render(cron_area, buf),
+            }
 
-            StatefulWidget::render(scrl_top, rights[0], buf, &mut self.comfylog_scrollbar_state);
+            StatefulWidget::render(
+                scrl_top,
+                comfy_area,
+                buf,
+                &mut self.comfylog_scrollbar_state,
+            );
             StatefulWidget::render(
                 scrl_bottom,
-                rights[1],
+                cron_area,
                 buf,
                 &mut self.cronlog_scrollbar_state,
             );
         }
-        let legend = match self.show_debugconsole {
-            true => " t : list task | k : kill all ".bold(),
-            false => {
-                " c : clear | Tab : switch panel | ▲ ▼ : scroll | Enter : activate | d : debug "
-                    .bold()
-            }
+        let legend = if self.shutdown.is_some() {
+            " stopping services before quitting | q : quit now ".bold()
+        } else if self.prompt.is_some() {
+            " Enter : next | ▲ ▼ : recent values | Ctrl-U : clear | Esc : cancel ".bold()
+        } else if self.attached.is_some_and(|m| logs.service(m).pty.is_some()) {
+            " typing goes to the terminal | Ctrl-] : detach ".bold()
+        } else if self.attached.is_some() {
+            " Enter : send | ▲ ▼ : history | Ctrl-D : EOF | Ctrl-U : clear | Esc : detach ".bold()
+        } else if self.view == View::Processes && self.explorer.filtering {
+            " type to filter | Enter : keep | Esc : clear ".bold()
+        } else if self.view == View::Processes {
+            " ▲ ▼ : select | / : filter | s : signal | Esc : back | p : close ".bold()
+        } else if self.view == View::Nodes {
+            " ▲ ▼ : select | u : update | a : update all | f : fetch | Esc : back | n : close "
+                .bold()
+        } else if self.view == View::Packages {
+            " ▲ ▼ : select | b : restore this snapshot | f : reload | Esc : back ".bold()
+        } else if self.view == View::History {
+            " ▲ ▼ : select | f : reload | Esc : back | h : close ".bold()
+        } else if self.view == View::Jobs {
+            " ▲ ▼ : select | x : cancel | Esc : back | j : close ".bold()
+        } else if self.view == View::Alerts {
+            " ▲ ▼ : select | c : clear | Esc : back | l : close ".bold()
+        } else if self.form.as_ref().is_some_and(|f| f.editing())
+            || self.editor.as_ref().is_some_and(|e| e.editing())
+        {
+            " Enter : set | Esc : cancel | Ctrl-U : clear ".bold()
+        } else if self.view == View::Command {
+            " ▲ ▼ : select | Enter : edit | ◀ ▶ : command | s : save | Esc : close ".bold()
+        } else if self.view == View::Args {
+            " ▲ ▼ : select | Space : toggle | ◀ ▶ : choose | Enter : edit | a : add | x : remove | s : save | Esc : close ".bold()
+        } else if self.view == View::Debug {
+            let actions = self
+                .config
+                .quick_actions
+                .iter()
+                .map(|a| format!("{} : {}", a.key, a.name))
+                .collect::<Vec<_>>();
+            format!(" {} ", actions.join(" | ")).bold()
+        } else if self.active_panel == ActivePanel::Menu {
+            " Enter : activate | e : edit | + : new | Del : delete | Shift ▲ ▼ : move | r : restart | Tab : switch panel | d : debug | a : args ".bold()
+        } else {
+            " c : clear | Tab : switch panel | ▲ ▼ : scroll | Enter : activate | i : input | r : restart | d : debug | a : args | p : processes | n : nodes | h : history | j : jobs | l : alerts ".bold()
         };
 
+        // first and left aligned when too wide, so the end of the legend is cut instead
+        let unread = match logs.alerts.unread() {
+            0 => None,
+            n => Some(format!(" {} unread alerts |", n).bold().red()),
+        };
+        let mut legend = Line::from_iter(unread.into_iter().chain([legend]));
+        if legend.width() > parent[1].width as usize {
+            legend = legend.left_aligned();
+        }
         let footer = Block::new()
             .borders(Borders::TOP)
             .title_alignment(Alignment::Center)
             .title(legend);
         footer.render(parent[1], buf);
+
+        logs.alerts.render_toasts(parent[0], buf);
+        if let Some(prompt) = &self.prompt {
+            prompt.render(area, buf);
+        }
+        if let Some(dialog) = &self.dialog {
+            dialog.render(area, buf);
+        }
+    }
+}
+
+/// spawns `future` and reports a panic or cancellation as an error entry in the debug console
+fn supervise<F>(logs: &Arc<RwLock<LogLists>>, name: &str, future: F) -> JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let logs = Arc::clone(logs);
+    let name = name.to_string();
+    let task = tokio::spawn(future);
+    tokio::spawn(async move {
+        if let Err(err) = task.await {
+            let reason = if err.is_panic() {
+                format!("panicked: {}", crash::payload_message(&*err.into_panic()))
+            } else {
+                "was cancelled".to_string()
+            };
+            logs.write()
+                .await
+                .debug_logs
+                .push(format!("[error] task {} {}", name, reason));
+        }
+    })
+}
+
+/// resolves when the tool itself is asked to terminate
+#[cfg(unix)]
+async fn termination_signal() -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut term = signal(SignalKind::terminate())?;
+    let mut hup = signal(SignalKind::hangup())?;
+    tokio::select! {
+        _ = term.recv() => {},
+        _ = hup.recv() => {},
     }
+    Ok(())
+}
+#[cfg(windows)]
+async fn termination_signal() -> Result<()> {
+    tokio::signal::windows::ctrl_close()?.recv().await;
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    // the launcher changes its environment, which is only sound before any thread runs
+    limits::launch();
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run())
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+async fn run() -> Result<()> {
+    let args = Args::parse();
+    if let Some(name) = &args.history {
+        print!("{}", history::report(name.as_deref()));
+        return Ok(());
+    }
+    if let Some(service) = &args.restart {
+        match control::send(&Request::Restart(service.clone())) {
+            Ok(reply) => println!("{}", reply),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    let config = match Config::new() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("invalid tool.json: {}", err);
+            std::process::exit(1);
+        }
+    };
     let terminal = ratatui::init();
-    let app_result = App::new().run(terminal).await;
+    crash::install_panic_hook();
+    let app_result = App::new(config, args).run(terminal).await;
     ratatui::restore();
     app_result
 }
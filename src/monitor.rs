@@ -0,0 +1,117 @@
+use crate::command::Thresholds;
+use crate::explorer::{format_bytes, process_tree};
+use std::collections::VecDeque;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// samples kept for the graphs, one per second
+const HISTORY: usize = 120;
+const MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sample {
+    pub cpu: f32,
+    pub rss: u64,
+    pub threads: usize,
+}
+
+/// usage history of one service, starts over when the service gets a new pid
+#[derive(Debug, Default)]
+pub struct Usage {
+    pid: Option<u32>,
+    pub current: Option<Sample>,
+    /// cpu percent per sample
+    pub cpu: VecDeque<u64>,
+    /// rss in MB per sample
+    pub rss: VecDeque<u64>,
+    over: u64,
+    tripped: bool,
+}
+
+impl Usage {
+    pub fn record(&mut self, pid: u32, sample: Sample) {
+        if self.pid != Some(pid) {
+            *self = Usage {
+                pid: Some(pid),
+                ..Usage::default()
+            };
+        }
+        self.current = Some(sample);
+        self.cpu.push_back(sample.cpu.round() as u64);
+        self.rss.push_back(sample.rss / MB);
+        if self.cpu.len() > HISTORY {
+            self.cpu.pop_front();
+            self.rss.pop_front();
+        }
+    }
+    /// the service is gone, the graphs stay until it runs again
+    pub fn stopped(&mut self) {
+        self.current = None;
+        self.over = 0;
+        self.tripped = false;
+    }
+    /// the exceeded limits once they held for `sustain` samples in a row,
+    /// then nothing until usage drops below them again
+    pub fn check(&mut self, thresholds: &Thresholds) -> Option<String> {
+        let sample = self.current?;
+        let mut exceeded = Vec::new();
+        if let Some(max) = thresholds.cpu_percent.filter(|max| sample.cpu > *max) {
+            exceeded.push(format!("cpu {:.0}% over {:.0}%", sample.cpu, max));
+        }
+        if let Some(max) = thresholds.rss_mb.filter(|max| sample.rss / MB > *max) {
+            exceeded.push(format!(
+                "rss {} over {}",
+                format_bytes(sample.rss),
+                format_bytes(max * MB)
+            ));
+        }
+        if exceeded.is_empty() {
+            self.over = 0;
+            self.tripped = false;
+            return None;
+        }
+        self.over += 1;
+        if self.tripped || self.over < thresholds.sustain.max(1) {
+            return None;
+        }
+        self.tripped = true;
+        Some(exceeded.join(", "))
+    }
+}
+
+/// reads cpu, memory and threads of whole process trees
+#[derive(Debug, Default)]
+pub struct Sampler {
+    system: System,
+}
+
+impl Sampler {
+    pub fn refresh(&mut self) {
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_cpu().with_memory(),
+        );
+    }
+    /// usage of `root` and its descendants summed up, `None` once `root` is gone
+    pub fn sample(&self, root: u32) -> Option<Sample> {
+        let processes = self.system.processes();
+        processes.get(&Pid::from_u32(root))?;
+        let tree = process_tree(
+            processes
+                .values()
+                .filter(|p| p.thread_kind().is_none())
+                .map(|p| (p.pid().as_u32(), p.parent().map(|pid| pid.as_u32()))),
+            &[root],
+        );
+        let sample = tree
+            .iter()
+            .filter_map(|pid| processes.get(&Pid::from_u32(*pid)))
+            .fold(Sample::default(), |sum, p| Sample {
+                cpu: sum.cpu + p.cpu_usage(),
+                rss: sum.rss + p.memory(),
+                // linux lists the threads besides the main one, elsewhere they are unknown
+                threads: sum.threads + 1 + p.tasks().map_or(0, |t| t.len()),
+            });
+        Some(sample)
+    }
+}
@@ -0,0 +1,193 @@
+use crate::git::RepoInfo;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{
+    Block, Borders, Paragraph, Row, StatefulWidget, Table, TableState, Widget, Wrap,
+};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepoState {
+    Idle,
+    Fetching,
+    Updating,
+    Installing,
+    UpToDate,
+    Updated,
+    Failed(String),
+}
+impl RepoState {
+    pub fn busy(&self) -> bool {
+        matches!(
+            self,
+            RepoState::Fetching | RepoState::Updating | RepoState::Installing
+        )
+    }
+}
+
+/// one git checkout below the custom nodes folder
+#[derive(Debug, Clone)]
+pub struct NodeRepo {
+    pub name: String,
+    pub path: String,
+    pub info: Option<RepoInfo>,
+    pub state: RepoState,
+    pub logs: Vec<String>,
+}
+impl NodeRepo {
+    pub fn push(&mut self, line: String, limit: usize) {
+        self.logs.push(line);
+        if self.logs.len() > limit {
+            self.logs.remove(0);
+        }
+    }
+}
+
+/// sub folders of `dir` that are git checkouts as `(name, path)`, sorted by name
+pub fn scan(dir: &str) -> io::Result<Vec<(String, String)>> {
+    let mut repos = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.join(".git").exists())
+        .map(|path| {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            (name, path.to_string_lossy().to_string())
+        })
+        .collect::<Vec<_>>();
+    repos.sort_by_key(|(name, _)| name.to_lowercase());
+    Ok(repos)
+}
+
+pub fn has_requirements(path: &str) -> bool {
+    Path::new(path).join("requirements.txt").exists()
+}
+
+/// selection of the custom nodes table, the repositories live in the shared logs
+#[derive(Debug, Default)]
+pub struct NodesPanel {
+    state: TableState,
+    selected: Option<String>,
+}
+
+impl NodesPanel {
+    pub fn selected<'a>(&self, repos: &'a [NodeRepo]) -> Option<&'a NodeRepo> {
+        let path = self.selected.as_ref()?;
+        repos.iter().find(|r| &r.path == path)
+    }
+    pub fn select(&mut self, repos: &[NodeRepo], step: isize) {
+        if repos.is_empty() {
+            return;
+        }
+        let current = self
+            .selected
+            .as_ref()
+            .and_then(|path| repos.iter().position(|r| &r.path == path));
+        let next = match current {
+            Some(i) => i.saturating_add_signed(step).min(repos.len() - 1),
+            None => 0,
+        };
+        self.selected = Some(repos[next].path.clone());
+    }
+    pub fn render(&mut self, block: Block, repos: &[NodeRepo], area: Rect, buf: &mut Buffer) {
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [table_area, log_area] =
+            Layout::vertical([Constraint::Min(3), Constraint::Percentage(35)]).areas(inner);
+        let index = self
+            .selected
+            .as_ref()
+            .and_then(|path| repos.iter().position(|r| &r.path == path));
+        let rows = repos
+            .iter()
+            .map(|r| {
+                let (branch, date, upstream) = match &r.info {
+                    Some(info) => (
+                        info.branch.clone(),
+                        info.date.clone(),
+                        match info.ahead_behind {
+                            Some((0, 0)) => "=".to_string(),
+                            Some((ahead, behind)) => format!("↑{} ↓{}", ahead, behind),
+                            None => "no upstream".to_string(),
+                        },
+                    ),
+                    None => Default::default(),
+                };
+                let behind = r.info.as_ref().and_then(|i| i.ahead_behind);
+                let row = Row::new(vec![
+                    r.name.clone(),
+                    branch,
+                    date,
+                    upstream,
+                    state(&r.state),
+                ]);
+                match &r.state {
+                    RepoState::Failed(_) => row.fg(Color::Red),
+                    state if state.busy() => row.fg(Color::Yellow),
+                    _ if behind.is_some_and(|(_, behind)| behind > 0) => row.fg(Color::Cyan),
+                    _ => row,
+                }
+            })
+            .collect::<Vec<_>>();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(2),
+                Constraint::Length(12),
+                Constraint::Length(11),
+                Constraint::Length(12),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                "REPOSITORY",
+                "BRANCH",
+                "COMMITTED",
+                "UPSTREAM",
+                "STATE",
+            ])
+            .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(
+            Style::new()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+        self.state.select(index);
+        StatefulWidget::render(table, table_area, buf, &mut self.state);
+        let Some(repo) = self.selected(repos) else {
+            return;
+        };
+        // newest lines at the bottom
+        let height = log_area.height.saturating_sub(1) as usize;
+        let lines = repo
+            .logs
+            .iter()
+            .skip(repo.logs.len().saturating_sub(height))
+            .map(|l| Line::raw(l.clone()))
+            .collect::<Vec<_>>();
+        Paragraph::new(lines)
+            .block(Block::new().borders(Borders::TOP).title(repo.name.as_str()))
+            .wrap(Wrap { trim: true })
+            .render(log_area, buf);
+    }
+}
+
+fn state(state: &RepoState) -> String {
+    match state {
+        RepoState::Idle => String::new(),
+        RepoState::Fetching => "fetching".to_string(),
+        RepoState::Updating => "updating".to_string(),
+        RepoState::Installing => "installing requirements".to_string(),
+        RepoState::UpToDate => "up to date".to_string(),
+        RepoState::Updated => "updated".to_string(),
+        RepoState::Failed(err) => format!("failed :{}", err),
+    }
+}
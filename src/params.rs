@@ -0,0 +1,288 @@
+use crate::command::{CmdInfo, Param, StepKind, TriggerAction};
+use crate::dialog::centered;
+use crate::input::{InputEvent, LineInput};
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear, Padding, Paragraph, Widget, Wrap};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+
+/// next to tool.json, the values last typed for every parameter
+const RECENT_FILE: &str = "params.json";
+const RECENT_LIMIT: usize = 10;
+
+/// recently used values, newest last, keyed by `<entry key>/<param name>`
+#[derive(Debug, Default)]
+pub struct Recent(HashMap<String, Vec<String>>);
+
+impl Recent {
+    pub fn load() -> Self {
+        let recent = fs::read_to_string(RECENT_FILE)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Recent(recent)
+    }
+    fn key(cmd: &CmdInfo, param: &Param) -> String {
+        format!("{}/{}", cmd.key(), param.name)
+    }
+    pub fn values(&self, cmd: &CmdInfo, param: &Param) -> &[String] {
+        self.0
+            .get(&Recent::key(cmd, param))
+            .map_or(&[], |v| v.as_slice())
+    }
+    /// the last value used, else the declared default
+    pub fn last(&self, cmd: &CmdInfo, param: &Param) -> String {
+        self.values(cmd, param)
+            .last()
+            .cloned()
+            .unwrap_or_else(|| param.default.clone())
+    }
+    pub fn remember(&mut self, cmd: &CmdInfo, values: &HashMap<String, String>) {
+        for param in &cmd.params {
+            let Some(value) = values.get(&param.name) else {
+                continue;
+            };
+            let list = self.0.entry(Recent::key(cmd, param)).or_default();
+            list.retain(|v| v != value);
+            list.push(value.clone());
+            if list.len() > RECENT_LIMIT {
+                list.remove(0);
+            }
+        }
+    }
+    pub fn save(&self) -> std::io::Result<()> {
+        fs::write(RECENT_FILE, serde_json::to_string_pretty(&self.0)?)
+    }
+}
+
+/// `cmd` with every `{name}` in `exe_path`, `work_dir`, `args`, the steps, the hooks and
+/// the programs of triggers replaced, no params left to ask
+pub fn substitute(cmd: &CmdInfo, values: &HashMap<String, String>) -> CmdInfo {
+    let replace = |text: &str| fill(text, values);
+    let mut filled = cmd.clone();
+    filled.exe_path = replace(&cmd.exe_path);
+    filled.work_dir = replace(&cmd.work_dir);
+    filled.args = cmd.args.iter().map(|a| replace(a)).collect();
+    for step in &mut filled.steps {
+        match &mut step.kind {
+            StepKind::Run {
+                exe_path,
+                args,
+                work_dir,
+            } => {
+                *exe_path = replace(exe_path);
+                *args = args.iter().map(|a| replace(a)).collect();
+                *work_dir = replace(work_dir);
+            }
+            StepKind::Git { work_dir, .. } => *work_dir = replace(work_dir),
+            StepKind::Service { .. } => {}
+        }
+    }
+    let triggered = filled
+        .triggers
+        .iter_mut()
+        .filter_map(|t| match &mut t.action {
+            TriggerAction::Run(hook) => Some(hook),
+            _ => None,
+        });
+    for hook in filled
+        .hooks
+        .all_mut()
+        .into_iter()
+        .flatten()
+        .chain(triggered)
+    {
+        hook.exe_path = replace(&hook.exe_path);
+        hook.args = hook.args.iter().map(|a| replace(a)).collect();
+        hook.work_dir = replace(&hook.work_dir);
+    }
+    filled.params.clear();
+    filled
+}
+
+/// `text` with every `{name}` of `values` replaced in one pass, so a value holding
+/// `{other}` stays as it was typed, unknown names are kept
+fn fill(text: &str, values: &HashMap<String, String>) -> String {
+    let mut filled = String::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after
+            .find('}')
+            .and_then(|close| Some((close, values.get(&after[..close])?)));
+        match value {
+            Some((close, value)) => {
+                filled.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = after;
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// `cmd` filled with the last used values, for starts nobody is asked about
+pub fn resolve(cmd: &CmdInfo) -> CmdInfo {
+    if cmd.params.is_empty() {
+        return cmd.clone();
+    }
+    let recent = Recent::load();
+    let values = cmd
+        .params
+        .iter()
+        .map(|p| (p.name.clone(), recent.last(cmd, p)))
+        .collect();
+    substitute(cmd, &values)
+}
+
+/// the value is one of the choices and matches the whole pattern
+pub fn validate(param: &Param, value: &str) -> Result<(), String> {
+    if !param.choices.is_empty() && !param.choices.iter().any(|c| c == value) {
+        return Err(format!("pick one of {}", param.choices.join(", ")));
+    }
+    if !param.pattern.is_empty() {
+        let pattern = Regex::new(&format!("^(?:{})$", param.pattern))
+            .map_err(|err| format!("invalid pattern :{}", err))?;
+        if !pattern.is_match(value) {
+            return Err(format!("must match {}", param.pattern));
+        }
+    }
+    Ok(())
+}
+
+/// what a key press in the prompt did
+#[derive(Debug, Clone)]
+pub enum PromptEvent {
+    /// every value is in, the command is ready to run
+    Done(Box<CmdInfo>),
+    Cancel,
+    Edited,
+}
+
+/// asks for the params of one command, one after another
+#[derive(Debug, Clone)]
+pub struct ParamPrompt {
+    pub cmd: CmdInfo,
+    /// menu entry the command was started from
+    pub index: usize,
+    current: usize,
+    values: HashMap<String, String>,
+    input: LineInput,
+    error: Option<String>,
+}
+
+impl ParamPrompt {
+    pub fn new(cmd: CmdInfo, index: usize, recent: &Recent) -> Self {
+        let mut prompt = ParamPrompt {
+            cmd,
+            index,
+            current: 0,
+            values: HashMap::new(),
+            input: LineInput::default(),
+            error: None,
+        };
+        prompt.ask(recent);
+        prompt
+    }
+    /// prefills the last value, ▲ ▼ go through the choices or the recent values
+    fn ask(&mut self, recent: &Recent) {
+        let param = &self.cmd.params[self.current];
+        let history = if param.choices.is_empty() {
+            recent.values(&self.cmd, param).to_vec()
+        } else {
+            param.choices.clone()
+        };
+        self.input = LineInput::with_history(history);
+        self.input.buffer = recent.last(&self.cmd, param);
+        self.error = None;
+    }
+    pub fn handle_key(&mut self, key: &KeyEvent, recent: &mut Recent) -> PromptEvent {
+        match self.input.handle_key(key) {
+            InputEvent::Submit(value) => {
+                let param = &self.cmd.params[self.current];
+                if let Err(err) = validate(param, &value) {
+                    self.input.buffer = value;
+                    self.error = Some(err);
+                    return PromptEvent::Edited;
+                }
+                self.values.insert(param.name.clone(), value);
+                self.current += 1;
+                if self.current < self.cmd.params.len() {
+                    self.ask(recent);
+                    return PromptEvent::Edited;
+                }
+                recent.remember(&self.cmd, &self.values);
+                PromptEvent::Done(Box::new(substitute(&self.cmd, &self.values)))
+            }
+            InputEvent::Cancel => PromptEvent::Cancel,
+            InputEvent::Eof | InputEvent::Edited => {
+                self.error = None;
+                PromptEvent::Edited
+            }
+        }
+    }
+}
+
+impl Widget for &ParamPrompt {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let param = &self.cmd.params[self.current];
+        let mut lines = vec![Line::raw(if param.prompt.is_empty() {
+            param.name.clone()
+        } else {
+            param.prompt.clone()
+        })];
+        if !param.choices.is_empty() {
+            lines.push(Line::raw(format!("choices :{}", param.choices.join(", "))).dark_gray());
+        }
+        if let Some(err) = &self.error {
+            lines.push(Line::raw(err.clone()).red());
+        }
+        let width = area.width.min(60);
+        let height = (lines.len() as u16 + 5).min(area.height);
+        let popup = centered(area, width, height);
+        Clear.render(popup, buf);
+        let block = Block::new()
+            .title(format!(
+                "{} ({}/{})",
+                self.cmd.name.trim(),
+                self.current + 1,
+                self.cmd.params.len()
+            ))
+            .borders(Borders::ALL)
+            .border_style(Color::Red)
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+        let [text, input] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(3)]).areas(inner);
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .render(text, buf);
+        self.input
+            .render(&format!("{{{}}}", param.name), input, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_does_not_expand_values_again() {
+        let values = HashMap::from([
+            ("a".to_string(), "{b}".to_string()),
+            ("b".to_string(), "x".to_string()),
+        ]);
+        assert_eq!(fill("{a}-{b}-{c}-{a{b}", &values), "{b}-x-{c}-{ax");
+    }
+}
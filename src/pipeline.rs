@@ -0,0 +1,66 @@
+use crate::command::Step;
+use tokio::sync::watch;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepStatus {
+    Pending,
+    Running,
+    Done,
+    Failed(String),
+    Skipped,
+    Cancelled,
+}
+
+/// progress of one run of a command's `steps`
+#[derive(Debug)]
+pub struct Pipeline {
+    pub steps: Vec<(String, StepStatus)>,
+    pub finished: bool,
+    cancel: watch::Sender<bool>,
+}
+
+impl Pipeline {
+    /// the receiver turns true once the run is cancelled
+    pub fn new(steps: &[Step]) -> (Self, watch::Receiver<bool>) {
+        let (cancel, cancelled) = watch::channel(false);
+        let pipeline = Pipeline {
+            steps: steps
+                .iter()
+                .map(|step| (step.label(), StepStatus::Pending))
+                .collect(),
+            finished: false,
+            cancel,
+        };
+        (pipeline, cancelled)
+    }
+    pub fn cancel(&self) {
+        let _ = self.cancel.send(true);
+    }
+    pub fn set(&mut self, index: usize, status: StepStatus) {
+        if let Some(step) = self.steps.get_mut(index) {
+            step.1 = status;
+        }
+    }
+    /// marks what never ran and ends the run
+    pub fn finish(&mut self) {
+        for step in &mut self.steps {
+            if step.1 == StepStatus::Pending {
+                step.1 = StepStatus::Skipped;
+            }
+        }
+        self.finished = true;
+    }
+    pub fn lines(&self) -> Vec<String> {
+        self.steps
+            .iter()
+            .map(|(name, status)| match status {
+                StepStatus::Pending => format!("· {}", name),
+                StepStatus::Running => format!("▶ {}", name),
+                StepStatus::Done => format!("✓ {}", name),
+                StepStatus::Failed(reason) => format!("✗ {} :{}", name, reason),
+                StepStatus::Skipped => format!("- {}", name),
+                StepStatus::Cancelled => format!("✗ {} :cancelled", name),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,280 @@
+use crate::command::CmdInfo;
+use crate::limits::Setup;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::sync::Mutex;
+
+const SCROLLBACK: usize = 1000;
+
+/// a child running under a pseudo terminal, its output is fed through a vt100 emulator
+pub struct PtySession {
+    parser: Mutex<vt100::Parser>,
+    master: Mutex<Box<dyn MasterPty + Send>>,
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl fmt::Debug for PtySession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PtySession").finish_non_exhaustive()
+    }
+}
+
+type Spawned = (
+    PtySession,
+    Box<dyn Child + Send + Sync>,
+    Box<dyn Read + Send>,
+);
+
+impl PtySession {
+    pub fn spawn(cmdinfo: &CmdInfo, setup: &Setup) -> io::Result<Spawned> {
+        let size = PtySize {
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+        };
+        let pair = native_pty_system()
+            .openpty(size)
+            .map_err(io::Error::other)?;
+        let argv = std::iter::once(cmdinfo.exe_path.clone())
+            .chain(cmdinfo.args.iter().cloned())
+            .collect::<Vec<_>>();
+        // the builder has no pre-exec hook, the tool limits the child as its launcher
+        #[cfg(unix)]
+        let (argv, launch) = setup.launcher(argv)?;
+        #[cfg(not(unix))]
+        let (launch, _) = (None::<[String; 2]>, setup);
+        let mut cmd = CommandBuilder::from_argv(argv.into_iter().map(Into::into).collect());
+        if let Some([key, value]) = launch {
+            cmd.env(key, value);
+        }
+        cmd.env("PYTHONUNBUFFERED", "1");
+        if !cmdinfo.work_dir.is_empty() {
+            cmd.cwd(&cmdinfo.work_dir);
+        }
+        let child = pair.slave.spawn_command(cmd).map_err(io::Error::other)?;
+        // the slave has to go away so reads end once the child exits
+        drop(pair.slave);
+        let reader = pair.master.try_clone_reader().map_err(io::Error::other)?;
+        let writer = pair.master.take_writer().map_err(io::Error::other)?;
+        let session = PtySession {
+            parser: Mutex::new(vt100::Parser::new(size.rows, size.cols, SCROLLBACK)),
+            master: Mutex::new(pair.master),
+            writer: Mutex::new(writer),
+        };
+        Ok((session, child, reader))
+    }
+    pub fn process(&self, bytes: &[u8]) {
+        if let Ok(mut parser) = self.parser.lock() {
+            parser.process(bytes);
+        }
+    }
+    pub fn write(&self, bytes: &[u8]) -> io::Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| io::Error::other("pty lock"))?;
+        writer.write_all(bytes)?;
+        writer.flush()
+    }
+    /// lines from the bottom of the screen to look back, 0 follows the output
+    pub fn scroll(&self, offset: usize) {
+        if let Ok(mut parser) = self.parser.lock() {
+            parser.set_scrollback(offset);
+        }
+    }
+    /// keeps the pty and the emulator the size of the panel that shows them
+    fn resize(&self, rows: u16, cols: u16) {
+        let (Ok(mut parser), Ok(master)) = (self.parser.lock(), self.master.lock()) else {
+            return;
+        };
+        if parser.screen().size() == (rows, cols) || rows == 0 || cols == 0 {
+            return;
+        }
+        parser.set_size(rows, cols);
+        let _ = master.resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        });
+    }
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.resize(area.height, area.width);
+        let Ok(parser) = self.parser.lock() else {
+            return;
+        };
+        let screen = parser.screen();
+        for row in 0..area.height {
+            for col in 0..area.width {
+                let Some(cell) = screen.cell(row, col) else {
+                    continue;
+                };
+                if cell.is_wide_continuation() {
+                    continue;
+                }
+                let target = &mut buf[(area.x + col, area.y + row)];
+                if cell.has_contents() {
+                    target.set_symbol(&cell.contents());
+                } else {
+                    target.set_symbol(" ");
+                }
+                target.set_style(cell_style(cell));
+            }
+        }
+        let (row, col) = screen.cursor_position();
+        if !screen.hide_cursor()
+            && screen.scrollback() == 0
+            && row < area.height
+            && col < area.width
+        {
+            let target = &mut buf[(area.x + col, area.y + row)];
+            target.set_style(Style::new().add_modifier(Modifier::REVERSED));
+        }
+    }
+    /// the bytes a terminal would send for `key`
+    pub fn encode_key(&self, key: &KeyEvent) -> Option<Vec<u8>> {
+        let app_cursor = self
+            .parser
+            .lock()
+            .map(|p| p.screen().application_cursor())
+            .unwrap_or(false);
+        let arrow = |c: u8| {
+            if app_cursor {
+                vec![0x1b, b'O', c]
+            } else {
+                vec![0x1b, b'[', c]
+            }
+        };
+        let bytes = match key.code {
+            KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let c = c.to_ascii_lowercase();
+                if c.is_ascii_lowercase() {
+                    vec![c as u8 & 0x1f]
+                } else {
+                    return None;
+                }
+            }
+            KeyCode::Char(c) => c.to_string().into_bytes(),
+            KeyCode::Enter => vec![b'\r'],
+            KeyCode::Backspace => vec![0x7f],
+            KeyCode::Tab => vec![b'\t'],
+            KeyCode::BackTab => b"\x1b[Z".to_vec(),
+            KeyCode::Esc => vec![0x1b],
+            KeyCode::Up => arrow(b'A'),
+            KeyCode::Down => arrow(b'B'),
+            KeyCode::Right => arrow(b'C'),
+            KeyCode::Left => arrow(b'D'),
+            KeyCode::Home => arrow(b'H'),
+            KeyCode::End => arrow(b'F'),
+            KeyCode::Insert => b"\x1b[2~".to_vec(),
+            KeyCode::Delete => b"\x1b[3~".to_vec(),
+            KeyCode::PageUp => b"\x1b[5~".to_vec(),
+            KeyCode::PageDown => b"\x1b[6~".to_vec(),
+            KeyCode::F(n @ 1..=4) => vec![0x1b, b'O', b'P' + n - 1],
+            KeyCode::F(n) => {
+                let code = match n {
+                    5 => 15,
+                    6 => 17,
+                    7 => 18,
+                    8 => 19,
+                    9 => 20,
+                    10 => 21,
+                    11 => 23,
+                    12 => 24,
+                    _ => return None,
+                };
+                format!("\x1b[{}~", code).into_bytes()
+            }
+            _ => return None,
+        };
+        if key.modifiers.contains(KeyModifiers::ALT) {
+            let mut prefixed = vec![0x1b];
+            prefixed.extend(bytes);
+            return Some(prefixed);
+        }
+        Some(bytes)
+    }
+}
+
+fn convert_color(color: vt100::Color) -> Color {
+    match color {
+        vt100::Color::Default => Color::Reset,
+        vt100::Color::Idx(i) => Color::Indexed(i),
+        vt100::Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
+
+fn cell_style(cell: &vt100::Cell) -> Style {
+    let mut style = Style::new()
+        .fg(convert_color(cell.fgcolor()))
+        .bg(convert_color(cell.bgcolor()));
+    if cell.bold() {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    if cell.italic() {
+        style = style.add_modifier(Modifier::ITALIC);
+    }
+    if cell.underline() {
+        style = style.add_modifier(Modifier::UNDERLINED);
+    }
+    if cell.inverse() {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    style
+}
+
+/// turns raw terminal output into plain log lines, escape sequences and carriage returns dropped
+#[derive(Debug, Default)]
+pub struct LineSplitter {
+    line: Vec<u8>,
+    escape: Escape,
+}
+
+#[derive(Debug, Default, PartialEq)]
+enum Escape {
+    #[default]
+    None,
+    Start,
+    Csi,
+    Osc,
+    OscEnd,
+}
+
+impl LineSplitter {
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        let mut lines = Vec::new();
+        for &b in bytes {
+            self.escape = match (&self.escape, b) {
+                (Escape::None, 0x1b) => Escape::Start,
+                (Escape::None, b'\n') => {
+                    lines.push(String::from_utf8_lossy(&self.line).trim_end().to_string());
+                    self.line.clear();
+                    Escape::None
+                }
+                (Escape::None, b'\r') => Escape::None,
+                (Escape::None, b) => {
+                    if b >= 0x20 || b == b'\t' {
+                        self.line.push(b);
+                    }
+                    Escape::None
+                }
+                (Escape::Start, b'[') => Escape::Csi,
+                (Escape::Start, b']') => Escape::Osc,
+                (Escape::Start, _) => Escape::None,
+                (Escape::Csi, 0x40..=0x7e) => Escape::None,
+                (Escape::Csi, _) => Escape::Csi,
+                (Escape::Osc, 0x07) => Escape::None,
+                (Escape::Osc, 0x1b) => Escape::OscEnd,
+                (Escape::Osc, _) => Escape::Osc,
+                (Escape::OscEnd, _) => Escape::None,
+            };
+        }
+        lines
+    }
+}
@@ -0,0 +1,271 @@
+use crate::command::MenuCommand;
+use crate::explorer::format_time;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{
+    Block, Borders, Paragraph, Row, StatefulWidget, Table, TableState, Widget, Wrap,
+};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::process::Command;
+
+/// next to tool.json, one `pip freeze` per file
+const DIR: &str = "snapshots";
+
+/// `pip freeze` of a service's interpreter at one point in time
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub service: MenuCommand,
+    /// unix millis
+    pub taken: u64,
+    pub label: String,
+    pub path: PathBuf,
+    /// normalized package name to its freeze line
+    pub packages: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added(String),
+    Removed(String),
+    Changed(String, String),
+}
+impl Change {
+    pub fn line(&self) -> String {
+        match self {
+            Change::Added(line) => format!("+ {}", line),
+            Change::Removed(line) => format!("- {}", line),
+            Change::Changed(from, to) => format!("~ {} -> {}", from, to),
+        }
+    }
+}
+
+/// `python -m pip freeze`
+pub async fn freeze(python: &str) -> Result<String, String> {
+    let output = Command::new(python)
+        .args(["-m", "pip", "freeze"])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|err| format!("failed to execute {} :{}", python, err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "pip freeze :{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// freezes `python` and stores it, the label goes into a comment line pip ignores
+pub async fn take(service: MenuCommand, python: &str, label: &str) -> Result<Snapshot, String> {
+    let frozen = freeze(python).await?;
+    let taken = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    let path = PathBuf::from(DIR).join(format!("{:?}-{}.txt", service, taken));
+    fs::create_dir_all(DIR).map_err(|err| format!("{} :{}", DIR, err))?;
+    fs::write(&path, format!("# {}\n{}", label, frozen))
+        .map_err(|err| format!("{} :{}", path.display(), err))?;
+    Ok(Snapshot {
+        service,
+        taken,
+        label: label.to_string(),
+        path,
+        packages: packages(&frozen),
+    })
+}
+
+/// every stored snapshot, oldest first
+pub fn list() -> Vec<Snapshot> {
+    let Ok(entries) = fs::read_dir(DIR) else {
+        return Vec::new();
+    };
+    let mut snapshots = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let stem = path.file_stem()?.to_string_lossy().to_string();
+            let (service, taken) = stem.rsplit_once('-')?;
+            let text = fs::read_to_string(&path).ok()?;
+            let label = text
+                .lines()
+                .next()
+                .and_then(|l| l.strip_prefix("# "))
+                .unwrap_or_default()
+                .to_string();
+            Some(Snapshot {
+                service: MenuCommand::from_str(service).ok()?,
+                taken: taken.parse().ok()?,
+                label,
+                packages: packages(&text),
+                path,
+            })
+        })
+        .collect::<Vec<_>>();
+    snapshots.sort_by_key(|s| s.taken);
+    snapshots
+}
+
+/// `name==1.0`, `name @ url` and editable installs keyed by their normalized name
+pub fn packages(frozen: &str) -> BTreeMap<String, String> {
+    frozen
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let name = line
+                .split_once("==")
+                .or_else(|| line.split_once(" @ "))
+                .map_or(line, |(name, _)| name);
+            (
+                name.trim().to_lowercase().replace('_', "-"),
+                line.to_string(),
+            )
+        })
+        .collect()
+}
+
+/// what turns `before` into `after`
+pub fn diff(before: &BTreeMap<String, String>, after: &BTreeMap<String, String>) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for (name, line) in before {
+        match after.get(name) {
+            None => changes.push(Change::Removed(line.clone())),
+            Some(now) if now != line => changes.push(Change::Changed(line.clone(), now.clone())),
+            Some(_) => {}
+        }
+    }
+    for (name, line) in after {
+        if !before.contains_key(name) {
+            changes.push(Change::Added(line.clone()));
+        }
+    }
+    changes
+}
+
+/// one line summary of `changes`
+pub fn summary(changes: &[Change]) -> String {
+    let count = |f: fn(&Change) -> bool| changes.iter().filter(|c| f(c)).count();
+    format!(
+        "{} added, {} removed, {} changed",
+        count(|c| matches!(c, Change::Added(_))),
+        count(|c| matches!(c, Change::Removed(_))),
+        count(|c| matches!(c, Change::Changed(..)))
+    )
+}
+
+/// snapshot list with the diff of the selected one against the one before it
+#[derive(Debug, Default)]
+pub struct PackagesPanel {
+    pub snapshots: Vec<Snapshot>,
+    state: TableState,
+    selected: Option<usize>,
+}
+
+impl PackagesPanel {
+    /// reads the snapshots again and selects the newest
+    pub fn reload(&mut self) {
+        self.snapshots = list();
+        self.selected = self.snapshots.len().checked_sub(1);
+    }
+    pub fn selected(&self) -> Option<&Snapshot> {
+        self.snapshots.get(self.selected?)
+    }
+    pub fn select(&mut self, step: isize) {
+        if self.snapshots.is_empty() {
+            return;
+        }
+        let next = match self.selected {
+            Some(i) => i.saturating_add_signed(step).min(self.snapshots.len() - 1),
+            None => 0,
+        };
+        self.selected = Some(next);
+    }
+    /// the latest earlier snapshot of the same service
+    pub fn previous(&self) -> Option<&Snapshot> {
+        let index = self.selected?;
+        let current = &self.snapshots[index];
+        self.snapshots[..index]
+            .iter()
+            .rev()
+            .find(|s| s.service == current.service)
+    }
+    pub fn render(&mut self, block: Block, area: Rect, buf: &mut Buffer) {
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [table_area, diff_area] =
+            Layout::vertical([Constraint::Percentage(40), Constraint::Min(3)]).areas(inner);
+        // newest on top
+        let rows = self
+            .snapshots
+            .iter()
+            .rev()
+            .map(|s| {
+                Row::new(vec![
+                    format_time(s.taken / 1000),
+                    format!("{:?}", s.service),
+                    s.packages.len().to_string(),
+                    s.label.clone(),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(17),
+                Constraint::Length(12),
+                Constraint::Length(9),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(vec!["TAKEN (UTC)", "SERVICE", "PACKAGES", "LABEL"])
+                .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(
+            Style::new()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+        self.state.select(
+            self.selected
+                .map(|i| self.snapshots.len().saturating_sub(i + 1)),
+        );
+        StatefulWidget::render(table, table_area, buf, &mut self.state);
+        let Some(current) = self.selected() else {
+            return;
+        };
+        let (title, lines) = match self.previous() {
+            Some(previous) => {
+                let changes = diff(&previous.packages, &current.packages);
+                let title = format!(
+                    "since {} :{}",
+                    format_time(previous.taken / 1000),
+                    summary(&changes)
+                );
+                let lines = changes
+                    .iter()
+                    .map(|c| match c {
+                        Change::Added(_) => Line::raw(c.line()).green(),
+                        Change::Removed(_) => Line::raw(c.line()).red(),
+                        Change::Changed(..) => Line::raw(c.line()).yellow(),
+                    })
+                    .collect::<Vec<_>>();
+                (title, lines)
+            }
+            None => ("first snapshot of this service".to_string(), Vec::new()),
+        };
+        Paragraph::new(lines)
+            .block(Block::new().borders(Borders::TOP).title(title))
+            .wrap(Wrap { trim: true })
+            .render(diff_area, buf);
+    }
+}
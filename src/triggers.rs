@@ -0,0 +1,231 @@
+use crate::command::{OutputTrigger, TriggerAction};
+use regex::{Captures, Regex};
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+/// a trigger of a service with its pattern compiled
+#[derive(Debug)]
+pub struct Armed {
+    pattern: Regex,
+    trigger: OutputTrigger,
+    /// when it last went off, quiet until the cooldown passed
+    fired: Option<Instant>,
+}
+
+/// compiles the patterns, `check_triggers` already refused invalid ones, programs
+/// without a `work_dir` run in the service's. a trigger armed before with the same
+/// settings keeps when it last went off, so a restart does not reset its cooldown
+pub fn arm(armed: &mut Vec<Armed>, triggers: &[OutputTrigger], work_dir: &str) {
+    let before = std::mem::take(armed);
+    *armed = triggers
+        .iter()
+        .filter_map(|trigger| {
+            let mut trigger = trigger.clone();
+            if let TriggerAction::Run(hook) = &mut trigger.action {
+                if hook.work_dir.is_empty() {
+                    hook.work_dir = work_dir.to_string();
+                }
+            }
+            let fired = before
+                .iter()
+                .find(|old| old.trigger == trigger)
+                .and_then(|old| old.fired);
+            Some(Armed {
+                pattern: Regex::new(&trigger.pattern).ok()?,
+                trigger,
+                fired,
+            })
+        })
+        .collect();
+}
+
+/// actions set off by an output line, capture groups filled in. `ready` has no
+/// cooldown, every instance of the service has to get ready
+pub fn check(armed: &mut [Armed], line: &str) -> Vec<TriggerAction> {
+    let now = Instant::now();
+    armed
+        .iter_mut()
+        .filter_map(|armed| {
+            let cooldown = Duration::from_secs(armed.trigger.cooldown);
+            if armed.trigger.action != TriggerAction::Ready
+                && armed
+                    .fired
+                    .is_some_and(|at| now.duration_since(at) < cooldown)
+            {
+                return None;
+            }
+            let caps = armed.pattern.captures(line)?;
+            armed.fired = Some(now);
+            Some(fill(&armed.trigger.action, &armed.pattern, &caps))
+        })
+        .collect()
+}
+
+/// the first `$1` or `${name}` in the texts of `action` that `pattern` has no group for
+pub fn unknown_group(action: &TriggerAction, pattern: &Regex) -> Option<String> {
+    let texts = match action {
+        TriggerAction::Toast(text) => vec![text],
+        TriggerAction::Run(hook) => [&hook.exe_path, &hook.work_dir]
+            .into_iter()
+            .chain(&hook.args)
+            .collect(),
+        _ => Vec::new(),
+    };
+    texts
+        .into_iter()
+        .flat_map(|text| references(text))
+        .find(|(_, name)| !has_group(pattern, name))
+        .map(|(_, name)| name.to_string())
+}
+
+/// the `$1` and `${name}` in `text` with where they are, any other `$` is plain text
+fn references(text: &str) -> Vec<(Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut rest = 0;
+    while let Some(at) = text[rest..].find('$').map(|at| rest + at) {
+        let after = &text[at + 1..];
+        let (name, len) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            (&after[..digits], digits)
+        };
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if valid {
+            found.push((at..at + 1 + len, name));
+            rest = at + 1 + len;
+        } else {
+            rest = at + 1;
+        }
+    }
+    found
+}
+
+fn has_group(pattern: &Regex, name: &str) -> bool {
+    match name.parse::<usize>() {
+        Ok(index) => index < pattern.captures_len(),
+        Err(_) => pattern.capture_names().flatten().any(|group| group == name),
+    }
+}
+
+fn fill(action: &TriggerAction, pattern: &Regex, caps: &Captures) -> TriggerAction {
+    let expand = |text: &str| {
+        let mut filled = String::new();
+        let mut copied = 0;
+        for (range, name) in references(text) {
+            if !has_group(pattern, name) {
+                continue;
+            }
+            let group = match name.parse::<usize>() {
+                Ok(index) => caps.get(index),
+                Err(_) => caps.name(name),
+            };
+            filled.push_str(&text[copied..range.start]);
+            filled.push_str(group.map_or("", |group| group.as_str()));
+            copied = range.end;
+        }
+        filled.push_str(&text[copied..]);
+        filled
+    };
+    match action {
+        TriggerAction::Toast(text) => TriggerAction::Toast(expand(text)),
+        TriggerAction::Run(hook) => {
+            let mut hook = hook.clone();
+            hook.exe_path = expand(&hook.exe_path);
+            hook.args = hook.args.iter().map(|a| expand(a)).collect();
+            hook.work_dir = expand(&hook.work_dir);
+            TriggerAction::Run(hook)
+        }
+        action => action.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn armed(json: &str) -> Vec<Armed> {
+        let triggers: Vec<OutputTrigger> = serde_json::from_str(json).unwrap();
+        let mut armed = Vec::new();
+        arm(&mut armed, &triggers, "/srv");
+        armed
+    }
+
+    #[test]
+    fn cooldown_keeps_a_trigger_quiet() {
+        let mut armed = armed(r#"[{ "pattern": "oom", "action": "restart", "cooldown": 60 }]"#);
+        assert_eq!(check(&mut armed, "oom"), [TriggerAction::Restart]);
+        assert!(check(&mut armed, "oom").is_empty());
+        armed[0].trigger.cooldown = 0;
+        assert_eq!(check(&mut armed, "oom"), [TriggerAction::Restart]);
+    }
+
+    #[test]
+    fn arming_again_keeps_the_cooldown_of_unchanged_triggers() {
+        let json = r#"[{ "pattern": "oom", "action": "bell" }]"#;
+        let mut armed = armed(json);
+        check(&mut armed, "oom");
+        let triggers: Vec<OutputTrigger> = serde_json::from_str(json).unwrap();
+        arm(&mut armed, &triggers, "/srv");
+        assert!(check(&mut armed, "oom").is_empty());
+        let changed = r#"[{ "pattern": "oom", "action": "bell", "cooldown": 5 }]"#;
+        let triggers: Vec<OutputTrigger> = serde_json::from_str(changed).unwrap();
+        arm(&mut armed, &triggers, "/srv");
+        assert_eq!(check(&mut armed, "oom"), [TriggerAction::Bell]);
+    }
+
+    #[test]
+    fn groups_are_filled_in() {
+        let mut armed = armed(
+            r#"[
+                { "pattern": "saved (\\S+) in (?<secs>\\d+)s", "action": { "toast": "$1 took ${secs}s, $5 ${x} $HOME cost $" } },
+                { "pattern": "saved (\\S+)", "action": { "run": { "exe_path": "cp", "args": ["$1", "/backup/${1}"] } } }
+            ]"#,
+        );
+        let actions = check(&mut armed, "saved a.png in 3s");
+        assert_eq!(
+            actions[0],
+            TriggerAction::Toast("a.png took 3s, $5 ${x} $HOME cost $".to_string())
+        );
+        let TriggerAction::Run(hook) = &actions[1] else {
+            panic!("not a run :{:?}", actions[1]);
+        };
+        assert_eq!(hook.args, ["a.png", "/backup/a.png"]);
+        assert_eq!(hook.work_dir, "/srv");
+    }
+
+    #[test]
+    fn ready_and_flash_come_back_as_they_are() {
+        let mut armed = armed(
+            r#"[
+                { "pattern": "listening", "action": "ready" },
+                { "pattern": "listening|warning", "action": "flash" }
+            ]"#,
+        );
+        assert_eq!(
+            check(&mut armed, "listening on :8188"),
+            [TriggerAction::Ready, TriggerAction::Flash]
+        );
+        // a restarted service has to get ready again, the flash waits for its cooldown
+        assert_eq!(
+            check(&mut armed, "listening on :8188"),
+            [TriggerAction::Ready]
+        );
+        assert!(check(&mut armed, "nothing").is_empty());
+    }
+
+    #[test]
+    fn unknown_groups_are_found() {
+        let pattern = Regex::new(r"saved (?<file>\S+)").unwrap();
+        let toast = |text: &str| TriggerAction::Toast(text.to_string());
+        assert_eq!(unknown_group(&toast("$1 ${file} $$ $HOME"), &pattern), None);
+        assert_eq!(
+            unknown_group(&toast("${name}"), &pattern),
+            Some("name".to_string())
+        );
+        assert_eq!(unknown_group(&toast("$2"), &pattern), Some("2".to_string()));
+    }
+}